@@ -320,8 +320,18 @@ pub enum Operation {
     MapAnonymousHandle,
     /// The view for an anonymouse mapping handle could not be created.
     MapAnonymousView,
+    /// Explicit huge pages could not be allocated for an anonymous mapping.
+    MapHugePage,
     /// A pointer could not be unmapped.
     Unmap,
+    /// A mapping could not be resized.
+    Remap,
+    /// A range of address space could not be reserved.
+    Reserve,
+    /// Pages within a reserved range could not be committed.
+    Commit,
+    /// Pages within a reserved range could not be decommitted.
+    Decommit,
     /// The [`Protect`] could not be applied to the provided memory region.
     ///
     /// [`Protect`]: ../enum.Protect.html
@@ -330,6 +340,8 @@ pub enum Operation {
     ///
     /// [`Advise`]: ../enum.Advise.html
     Advise,
+    /// The residency of the physical pages could not be queried.
+    Resident,
     /// The physical page could not be locked into memory.
     Lock,
     /// The physical page could not be unlocked from memory.
@@ -348,6 +360,10 @@ pub enum Operation {
     RingSecondary,
     /// A temporary memory file descriptor failed to open.
     MemoryFd,
+    /// A file descriptor could not be sent over a socket.
+    SendFd,
+    /// A file descriptor could not be received from a socket.
+    ReceiveFd,
     /// Used for pure I/O errors to simplify wrapping a `std::io::Error` into an
     ///
     /// [`Error`]: struct.Error.html
@@ -379,9 +395,15 @@ impl Operation {
             Operation::MapAnonymous => Some("map anonymous"),
             Operation::MapAnonymousHandle => Some("map anonymous handle"),
             Operation::MapAnonymousView => Some("map anonymous view"),
+            Operation::MapHugePage => Some("map huge pages"),
             Operation::Unmap => Some("unmap"),
+            Operation::Remap => Some("resize mapped memory"),
+            Operation::Reserve => Some("reserve address space"),
+            Operation::Commit => Some("commit reserved memory"),
+            Operation::Decommit => Some("decommit reserved memory"),
             Operation::Protect => Some("protect mapped memory"),
             Operation::Advise => Some("advise mapped memory"),
+            Operation::Resident => Some("query resident memory"),
             Operation::Lock => Some("lock mapped memory"),
             Operation::Unlock => Some("unlock mapped memory"),
             Operation::Flush => Some("flush mapped memory"),
@@ -391,6 +413,8 @@ impl Operation {
             Operation::RingPrimary => Some("map ring first half"),
             Operation::RingSecondary => Some("map ring second half"),
             Operation::MemoryFd => Some("open memory fd"),
+            Operation::SendFd => Some("send fd"),
+            Operation::ReceiveFd => Some("receive fd"),
             Operation::None => None,
         }
     }
@@ -415,6 +439,12 @@ impl fmt::Display for Operation {
 pub enum Input {
     /// The range of the requested file or bytes is invalid.
     InvalidRange,
+    /// The header of a shared or persistent resource is invalid.
+    InvalidHeader,
+    /// The memory file descriptor is missing one or more required seals.
+    MissingSeals,
+    /// The advise may discard memory, so it requires exclusive access.
+    DestructiveAdvise,
 }
 
 impl Input {
@@ -437,6 +467,9 @@ impl Input {
     pub fn as_str(&self) -> &'static str {
         match *self {
             Input::InvalidRange => "invalid range",
+            Input::InvalidHeader => "invalid header",
+            Input::MissingSeals => "missing required seals",
+            Input::DestructiveAdvise => "destructive advise requires exclusive access",
         }
     }
 }
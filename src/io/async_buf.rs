@@ -0,0 +1,482 @@
+use super::{Ring, SeqRead, SeqWrite};
+use crate::Result;
+
+use std::{
+    io::{self, BufRead, ErrorKind},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// The `AsyncBufReader` adds buffering to any asynchronous reader using a
+/// specialized buffer.
+///
+/// This is the asynchronous counterpart to [`BufReader`]. It is very similar
+/// to `tokio::io::BufReader`, but it uses a [`Ring`] for the internal buffer,
+/// and it provides a configurable low water mark. The inner reader must be
+/// `Unpin`.
+///
+/// The `tokio` feature implements the `tokio::io` traits, and the `futures-io`
+/// feature implements the `futures_io` traits. In both cases the readable
+/// region of the [`Ring`] is returned directly from `poll_fill_buf()`
+/// without copying.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::AsyncBufReader;
+/// use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+///
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// # #[cfg(feature = "tokio")]
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let (mut cli, srv) = tokio::io::duplex(64);
+/// let mut buf = AsyncBufReader::new(srv, 4000).expect("failed to create buffer");
+/// cli.write_all(b"hello\nworld\n").await?;
+/// let mut line = String::new();
+/// buf.read_line(&mut line).await?;
+/// assert_eq!(line, "hello\n");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`BufReader`]: struct.BufReader.html
+/// [`Ring`]: struct.Ring.html
+pub struct AsyncBufReader<R> {
+    buf: Ring,
+    inner: R,
+    lowat: usize,
+}
+
+impl<R> AsyncBufReader<R> {
+    /// Creates a new `AsyncBufReader`.
+    pub fn new(inner: R, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            buf: Ring::new(capacity)?,
+            inner,
+            lowat: 0,
+        })
+    }
+
+    /// Get the low-water level.
+    #[inline]
+    pub fn lowat(&self) -> usize {
+        self.lowat
+    }
+
+    /// Set the low-water level.
+    ///
+    /// When the internal buffer content length drops to this level or below, a
+    /// subsequent call to `poll_fill_buf()` will request more from the inner
+    /// reader.
+    #[inline]
+    pub fn set_lowat(&mut self, val: usize) {
+        self.lowat = val
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the internally buffered data.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        self.buf.as_read_slice(usize::MAX)
+    }
+
+    /// Unwraps this `AsyncBufReader`, returning the underlying reader.
+    ///
+    /// Any buffered data is discarded.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// The `AsyncBufWriter` adds buffering to any asynchronous writer using a
+/// specialized buffer.
+///
+/// This is the asynchronous counterpart to [`BufWriter`]. It is very similar
+/// to `tokio::io::BufWriter`, but it uses a [`Ring`] for the internal buffer.
+/// The inner writer must be `Unpin`. As with the [`AsyncBufReader`], the
+/// `tokio` and `futures-io` features select which traits are implemented.
+///
+/// While the inner writer is applying backpressure, writes continue to fill
+/// the buffer, and a write only returns `Poll::Pending` once the buffer is
+/// full. Unlike the synchronous [`BufWriter`], the buffer is not flushed on
+/// drop, so `flush()` or `shutdown()` must be awaited before it is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::{AsyncBufReader, AsyncBufWriter};
+/// use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+///
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// # #[cfg(feature = "tokio")]
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let (send, recv) = tokio::io::duplex(64);
+///
+/// let mut wr = AsyncBufWriter::new(send, 4000).unwrap();
+/// wr.write_all(b"hello\nworld\n").await?;
+/// wr.flush().await?;
+///
+/// let mut rd = AsyncBufReader::new(recv, 4000).unwrap();
+/// let mut line = String::new();
+/// rd.read_line(&mut line).await?;
+/// assert_eq!(line, "hello\n");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`AsyncBufReader`]: struct.AsyncBufReader.html
+/// [`BufWriter`]: struct.BufWriter.html
+/// [`Ring`]: struct.Ring.html
+pub struct AsyncBufWriter<W> {
+    buf: Ring,
+    inner: W,
+}
+
+impl<W> AsyncBufWriter<W> {
+    /// Creates a new `AsyncBufWriter`.
+    pub fn new(inner: W, capacity: usize) -> Result<Self> {
+        Ok(Self::from_parts(inner, Ring::new(capacity)?))
+    }
+
+    /// Creates a new `AsyncBufWriter` using an allocated, and possibly
+    /// populated, [`Ring`] instance. Consider calling [`Ring::clear()`] prior
+    /// if the contents of the ring should be discarded.
+    ///
+    /// [`Ring`]: struct.Ring.html
+    /// [`Ring::clear()`]: struct.Ring.html#method.clear
+    pub fn from_parts(inner: W, buf: Ring) -> Self {
+        Self { buf, inner }
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the internally buffered data.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        self.buf.as_read_slice(usize::MAX)
+    }
+
+    /// Disassembles this `AsyncBufWriter` into the underlying writer and the
+    /// [`Ring`] used for buffering, containing any buffered but unwritten data.
+    ///
+    /// [`Ring`]: struct.Ring.html
+    pub fn into_parts(self) -> (W, Ring) {
+        (self.inner, self.buf)
+    }
+}
+
+impl<R: Unpin> AsyncBufReader<R> {
+    fn poll_fill_with<F>(&mut self, cx: &mut Context<'_>, read: F) -> Poll<io::Result<&[u8]>>
+    where
+        F: FnOnce(Pin<&mut R>, &mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+    {
+        if self.buf.read_len() <= self.lowat && self.buf.write_len() > 0 {
+            let dst = self.buf.as_write_slice(usize::MAX);
+            match read(Pin::new(&mut self.inner), cx, dst) {
+                Poll::Ready(Ok(n)) => self.buf.feed(n),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                // Only wait when there is nothing buffered to hand out.
+                Poll::Pending if self.buf.is_empty() => return Poll::Pending,
+                Poll::Pending => {}
+            }
+        }
+        Poll::Ready(Ok(self.buf.as_read_slice(usize::MAX)))
+    }
+
+    fn is_bypass(&self, len: usize) -> bool {
+        // If the reader has been dequeued and the destination buffer is larger
+        // than the internal buffer, then read directly into the destination.
+        self.buf.read_len() == 0 && len >= self.buf.write_capacity()
+    }
+}
+
+impl<W: Unpin> AsyncBufWriter<W> {
+    fn poll_flush_buf<F>(&mut self, cx: &mut Context<'_>, write: F) -> Poll<io::Result<()>>
+    where
+        F: Fn(Pin<&mut W>, &mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+    {
+        while !self.buf.is_empty() {
+            let src = self.buf.as_read_slice(usize::MAX);
+            match ready!(write(Pin::new(&mut self.inner), cx, src)) {
+                Ok(0) => return Poll::Ready(Err(ErrorKind::WriteZero.into())),
+                Ok(n) => self.buf.consume(n),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_write_with<F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        write: F,
+    ) -> Poll<io::Result<usize>>
+    where
+        F: Fn(Pin<&mut W>, &mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+    {
+        if buf.len() > self.buf.write_len() {
+            match self.poll_flush_buf(cx, &write) {
+                Poll::Ready(r) => r?,
+                // Keep buffering until the ring is full.
+                Poll::Pending if self.buf.write_len() == 0 => return Poll::Pending,
+                Poll::Pending => {}
+            }
+        }
+        if self.buf.is_empty() && buf.len() >= self.buf.write_capacity() {
+            write(Pin::new(&mut self.inner), cx, buf)
+        } else {
+            Poll::Ready(self.buf.write_into(buf))
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_impl {
+    use super::{AsyncBufReader, AsyncBufWriter};
+
+    use std::{
+        io,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
+
+    fn read<R: AsyncRead>(
+        inner: Pin<&mut R>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut rd = ReadBuf::new(buf);
+        ready!(inner.poll_read(cx, &mut rd))?;
+        Poll::Ready(Ok(rd.filled().len()))
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncRead for AsyncBufReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            if this.is_bypass(buf.remaining()) {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            }
+            let rem = ready!(this.poll_fill_with(cx, read))?;
+            let len = rem.len().min(buf.remaining());
+            buf.put_slice(&rem[..len]);
+            io::BufRead::consume(&mut this.buf, len);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncBufRead for AsyncBufReader<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            self.get_mut().poll_fill_with(cx, read)
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            io::BufRead::consume(&mut self.get_mut().buf, amt);
+        }
+    }
+
+    impl<R: AsyncRead + AsyncWrite + Unpin> AsyncWrite for AsyncBufReader<R> {
+        #[inline]
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+        }
+
+        #[inline]
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+        }
+
+        #[inline]
+        fn is_write_vectored(&self) -> bool {
+            self.inner.is_write_vectored()
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        #[inline]
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncBufWriter<W> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.get_mut()
+                .poll_write_with(cx, buf, <W as AsyncWrite>::poll_write)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            ready!(this.poll_flush_buf(cx, <W as AsyncWrite>::poll_write))?;
+            Pin::new(&mut this.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            ready!(this.poll_flush_buf(cx, <W as AsyncWrite>::poll_write))?;
+            Pin::new(&mut this.inner).poll_shutdown(cx)
+        }
+    }
+
+    impl<W: AsyncWrite + AsyncRead + Unpin> AsyncRead for AsyncBufWriter<W> {
+        #[inline]
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_impl {
+    use super::{AsyncBufReader, AsyncBufWriter};
+    use crate::io::SeqRead;
+
+    use std::{
+        io,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+    impl<R: AsyncRead + Unpin> AsyncRead for AsyncBufReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            if this.is_bypass(buf.len()) {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            }
+            ready!(this.poll_fill_with(cx, <R as AsyncRead>::poll_read))?;
+            Poll::Ready(this.buf.read_from(buf))
+        }
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncBufRead for AsyncBufReader<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            self.get_mut()
+                .poll_fill_with(cx, <R as AsyncRead>::poll_read)
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            io::BufRead::consume(&mut self.get_mut().buf, amt);
+        }
+    }
+
+    impl<R: AsyncRead + AsyncWrite + Unpin> AsyncWrite for AsyncBufReader<R> {
+        #[inline]
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+        }
+
+        #[inline]
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        #[inline]
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_close(cx)
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncBufWriter<W> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.get_mut()
+                .poll_write_with(cx, buf, <W as AsyncWrite>::poll_write)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            ready!(this.poll_flush_buf(cx, <W as AsyncWrite>::poll_write))?;
+            Pin::new(&mut this.inner).poll_flush(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            ready!(this.poll_flush_buf(cx, <W as AsyncWrite>::poll_write))?;
+            Pin::new(&mut this.inner).poll_close(cx)
+        }
+    }
+
+    impl<W: AsyncWrite + AsyncRead + Unpin> AsyncRead for AsyncBufWriter<W> {
+        #[inline]
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+        }
+    }
+}
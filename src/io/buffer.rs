@@ -3,8 +3,9 @@ use crate::Result;
 
 use std::{
     fmt,
-    io::{self, BufRead, ErrorKind, Read, Write},
+    io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom, Write},
     ops::{Deref, DerefMut},
+    slice,
 };
 
 /// The `BufReader` adds buffering to any reader using a specialized buffer.
@@ -89,13 +90,147 @@ impl<R: Read> BufReader<R> {
     /// Returns a reference to the internally buffered data.
     #[inline]
     pub fn buffer(&self) -> &[u8] {
-        self.buf.as_read_slice(std::usize::MAX)
+        self.buf.as_read_slice(usize::MAX)
+    }
+
+    /// Returns the number of bytes the internal buffer can hold at once.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.write_capacity()
+    }
+
+    /// Reads until at least `len` bytes are buffered or the end of the
+    /// stream is reached, returning all buffered data.
+    ///
+    /// Unlike [`fill_buf()`], which performs at most one read, this continues
+    /// to read from the inner reader until the requested length is satisfied.
+    /// The returned slice is always contiguous, so it may be used to parse
+    /// headers without copying. The slice will be shorter than `len` only if
+    /// the end of the stream was reached. An error with a kind of
+    /// `InvalidInput` is returned if `len` exceeds the buffer capacity.
+    ///
+    /// [`fill_buf()`]: #method.fill_buf
+    pub fn fill_at_least(&mut self, len: usize) -> io::Result<&[u8]> {
+        if len > self.buf.write_capacity() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "length exceeds buffer capacity",
+            ));
+        }
+        while self.buf.read_len() < len {
+            match self.inner.read(self.buf.as_write_slice(usize::MAX)) {
+                Ok(0) => break,
+                Ok(n) => self.buf.feed(n),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.buffer())
+    }
+
+    /// Gets the next `len` bytes without consuming them.
+    ///
+    /// This uses [`fill_at_least()`] to buffer the bytes, so the returned
+    /// slice is shorter than `len` only if the end of the stream was reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::BufReader;
+    /// use std::io::BufRead;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut buf = BufReader::new(&b"\x00\x05hello"[..], 4000)?;
+    /// let hdr = buf.peek(2)?;
+    /// let len = u16::from_be_bytes([hdr[0], hdr[1]]) as usize;
+    /// buf.consume(2);
+    /// assert_eq!(buf.peek(len)?, b"hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`fill_at_least()`]: #method.fill_at_least
+    pub fn peek(&mut self, len: usize) -> io::Result<&[u8]> {
+        let buf = self.fill_at_least(len)?;
+        Ok(&buf[..len.min(buf.len())])
+    }
+
+    /// Reads the next line without copying it.
+    ///
+    /// This behaves like [`next_until()`] using a newline delimiter, except
+    /// that the returned line does not include the trailing `\n` or `\r\n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::BufReader;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut buf = BufReader::new(&b"hello\r\nworld"[..], 4000)?;
+    /// assert_eq!(buf.next_line()?, Some(&b"hello"[..]));
+    /// assert_eq!(buf.next_line()?, Some(&b"world"[..]));
+    /// assert_eq!(buf.next_line()?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`next_until()`]: #method.next_until
+    pub fn next_line(&mut self) -> io::Result<Option<&[u8]>> {
+        Ok(self.next_until(b'\n')?.map(|line| {
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            line.strip_suffix(b"\r").unwrap_or(line)
+        }))
+    }
+
+    /// Reads up to and including the next `delim` byte without copying.
+    ///
+    /// The buffered data is searched first, and more is read from the inner
+    /// reader until the delimiter is found. The returned slice is consumed
+    /// from the buffer and remains valid until the next call on the reader.
+    /// At the end of the stream, any remaining data is returned without a
+    /// delimiter, and `None` is returned once it is exhausted.
+    ///
+    /// The entire slice must fit within the buffer. If the buffer fills
+    /// without finding the delimiter, an error with a kind of `InvalidData` is
+    /// returned and the buffered data is left unconsumed.
+    pub fn next_until(&mut self, delim: u8) -> io::Result<Option<&[u8]>> {
+        let mut searched = 0;
+        let end = loop {
+            let buf = self.buffer();
+            if let Some(i) = memchr::memchr(delim, &buf[searched..]) {
+                break searched + i + 1;
+            }
+            searched = buf.len();
+            if searched == self.buf.write_capacity() {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "delimiter not found within buffer capacity",
+                ));
+            }
+            match self.inner.read(self.buf.as_write_slice(usize::MAX)) {
+                Ok(0) if searched == 0 => return Ok(None),
+                Ok(0) => break searched,
+                Ok(n) => self.buf.feed(n),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        let ptr = self.buf.as_read_slice(end).as_ptr();
+        self.buf.consume(end);
+        // SAFETY: consuming only advances the read position. The bytes are not
+        // overwritten until the next read, which requires a mutable borrow that
+        // cannot occur while the returned slice is alive.
+        Ok(Some(unsafe { slice::from_raw_parts(ptr, end) }))
     }
 
     /// Unwraps this `BufReader`, returning the underlying reader.
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    pub(super) fn into_parts(self) -> (R, Ring) {
+        (self.inner, self.buf)
+    }
 }
 
 impl<R: Read> Deref for BufReader<R> {
@@ -180,7 +315,7 @@ impl<R: Read + Write> Write for BufReader<R> {
 impl<R: Read> BufRead for BufReader<R> {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
         if self.buf.read_len() <= self.lowat {
-            let n = self.inner.read(self.buf.as_write_slice(std::usize::MAX))?;
+            let n = self.inner.read(self.buf.as_write_slice(usize::MAX))?;
             self.buf.feed(n);
         }
         Ok(self.buffer())
@@ -191,6 +326,63 @@ impl<R: Read> BufRead for BufReader<R> {
     }
 }
 
+impl<R: Read + Seek> BufReader<R> {
+    /// Seeks relative to the current position.
+    ///
+    /// If the new position lies within the buffered data, the read position
+    /// of the buffer is moved and the inner reader is not seeked at all.
+    /// Otherwise, this behaves the same as seeking with `SeekFrom::Current`.
+    pub fn seek_relative(&mut self, offset: i64) -> io::Result<()> {
+        match usize::try_from(offset) {
+            Ok(n) if n <= self.buf.read_len() => {
+                self.buf.consume(n);
+                Ok(())
+            }
+            _ => self.seek(SeekFrom::Current(offset)).map(drop),
+        }
+    }
+}
+
+impl<R: Read + Seek> Seek for BufReader<R> {
+    /// Seek to an offset, in bytes, in the underlying reader.
+    ///
+    /// This follows the semantics of `std::io::BufReader`. The position used
+    /// for `SeekFrom::Current` is the position of the buffered reader rather
+    /// than the inner reader. A relative seek that stays within the buffered
+    /// data moves the read position of the buffer instead of discarding it.
+    /// Any other seek discards the buffer.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let remainder = self.buf.read_len() as i64;
+        let result = match pos {
+            SeekFrom::Current(n) if (0..=remainder).contains(&n) => {
+                self.buf.consume(n as usize);
+                return self.stream_position();
+            }
+            SeekFrom::Current(n) => match n.checked_sub(remainder) {
+                Some(off) => self.inner.seek(SeekFrom::Current(off))?,
+                None => {
+                    // Seek back over the buffered data first to avoid overflow.
+                    self.inner.seek(SeekFrom::Current(-remainder))?;
+                    self.buf.clear();
+                    self.inner.seek(SeekFrom::Current(n))?
+                }
+            },
+            _ => self.inner.seek(pos)?,
+        };
+        self.buf.clear();
+        Ok(result)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        let remainder = self.buf.read_len() as u64;
+        self.inner.stream_position().map(|pos| {
+            pos.checked_sub(remainder).expect(
+                "overflow when subtracting remaining buffer size from inner stream position",
+            )
+        })
+    }
+}
+
 /// The `BufWriter` adds buffering to any writer using a specialized buffer.
 ///
 /// This is very similar `std::io::BufWriter`, but it uses a [`Ring`] for the
@@ -353,14 +545,84 @@ impl<W: Write> BufWriter<W> {
         (inner, buf)
     }
 
-    fn flush_buf(&mut self) -> io::Result<()> {
+    /// Reserves at least `len` contiguous bytes in the buffer for writing.
+    ///
+    /// The buffer is flushed if there is not enough space available. The
+    /// returned slice covers all writable space, which may be larger than
+    /// `len`. Bytes written into the slice are not part of the output until
+    /// they are passed to [`.commit()`]. An error with a kind of
+    /// `InvalidInput` is returned if `len` exceeds the buffer capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::BufWriter;
+    /// # use std::io::Write;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut wr = BufWriter::new(Vec::new(), 4000)?;
+    /// let buf = wr.reserve(8)?;
+    /// buf[..8].copy_from_slice(&42u64.to_be_bytes());
+    /// wr.commit(8);
+    /// wr.flush()?;
+    /// assert_eq!(wr.get_ref(), &42u64.to_be_bytes());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.commit()`]: #method.commit
+    pub fn reserve(&mut self, len: usize) -> io::Result<&mut [u8]> {
+        if len > self.buf.write_capacity() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "length exceeds buffer capacity",
+            ));
+        }
+        if self.buf.write_len() < len {
+            self.flush_buf()?;
+        }
+        Ok(self.buf.as_write_slice(usize::MAX))
+    }
+
+    /// Commits `len` bytes written into the slice from [`.reserve()`].
+    ///
+    /// The length is limited to the available space in the buffer.
+    ///
+    /// [`.reserve()`]: #method.reserve
+    #[inline]
+    pub fn commit(&mut self, len: usize) {
+        self.buf.feed(len);
+    }
+
+    pub(super) fn buffer(&self) -> &[u8] {
+        self.buf.as_read_slice(usize::MAX)
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.buf.write_capacity()
+    }
+
+    /// Copies as much of `buf` as fits into the buffer without flushing.
+    pub(super) fn write_to_buf(&mut self, buf: &[u8]) -> usize {
+        self.buf.write_into(buf).unwrap_or(0)
+    }
+
+    /// Writes directly to the inner writer, bypassing the buffer.
+    pub(super) fn write_inner(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.panicked = true;
+        let r = self.inner.write(buf);
+        self.panicked = false;
+        r
+    }
+
+    pub(super) fn flush_buf(&mut self) -> io::Result<()> {
         loop {
             if self.buf.is_empty() {
                 break Ok(());
             }
 
             self.panicked = true;
-            let r = self.inner.write(self.buf.as_read_slice(std::usize::MAX));
+            let r = self.inner.write(self.buf.as_read_slice(usize::MAX));
             self.panicked = false;
 
             match r {
@@ -424,11 +686,8 @@ impl<W: Write> Write for BufWriter<W> {
         if buf.len() > self.buf.write_len() {
             self.flush_buf()?;
         }
-        if buf.len() >= self.buf.write_len() {
-            self.panicked = true;
-            let r = self.inner.write(buf);
-            self.panicked = false;
-            r
+        if buf.len() >= self.buf.write_capacity() {
+            self.write_inner(buf)
         } else {
             self.buf.write(buf)
         }
@@ -466,6 +725,16 @@ impl<W: Write + Read> Read for BufWriter<W> {
     }
 }
 
+impl<W: Write + Seek> Seek for BufWriter<W> {
+    /// Seek to the offset, in bytes, in the underlying writer.
+    ///
+    /// Seeking always writes out the internal buffer before seeking.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.flush_buf()?;
+        self.get_mut().seek(pos)
+    }
+}
+
 /// An error returned by [`BufWriter::into_inner`] which combines an error that
 /// happened while writing out the buffer, and the buffered writer object
 /// which may be used to recover from the condition.
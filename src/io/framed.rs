@@ -0,0 +1,365 @@
+//! Length-delimited framing over ring buffers.
+//!
+//! A [`FrameReader`] wraps a [`BufReader`] and yields each frame as a slice
+//! borrowed directly from the [`Ring`], so frames are never copied. Because
+//! the ring is mirrored, a frame is always contiguous even when it wraps
+//! around the end of the buffer. A [`FrameWriter`] wraps a [`BufWriter`] and
+//! can encode a frame in place by reserving space for the length prefix and
+//! back-filling it once the payload has been written.
+//!
+//! # Examples
+//!
+//! ```
+//! use vmap::io::framed::{Endian, FrameReader, FrameWriter, Prefix};
+//! use vmap::io::{BufReader, BufWriter};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let prefix = Prefix::U16(Endian::Big);
+//! let mut wr = FrameWriter::new(BufWriter::new(Vec::new(), 4000)?, prefix);
+//! wr.write_frame(b"hello")?;
+//! wr.write_frame_with(100, |buf| {
+//!     buf[..5].copy_from_slice(b"world");
+//!     Ok(5)
+//! })?;
+//! let data = wr.into_inner().into_inner().map_err(|e| e.into_error())?;
+//! assert_eq!(&data[..7], b"\x00\x05hello");
+//!
+//! let mut rd = FrameReader::new(BufReader::new(&data[..], 4000)?, prefix);
+//! assert_eq!(rd.next_frame()?, Some(&b"hello"[..]));
+//! assert_eq!(rd.next_frame()?, Some(&b"world"[..]));
+//! assert_eq!(rd.next_frame()?, None);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`BufReader`]: ../struct.BufReader.html
+//! [`BufWriter`]: ../struct.BufWriter.html
+//! [`FrameReader`]: struct.FrameReader.html
+//! [`FrameWriter`]: struct.FrameWriter.html
+//! [`Ring`]: ../struct.Ring.html
+
+use super::{BufReader, BufWriter};
+
+use std::io::{self, BufRead, ErrorKind, Read, Write};
+
+/// Byte order of a fixed-size length prefix.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Endian {
+    /// Most significant byte first.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+/// Encoding of the length prefix for each frame.
+///
+/// The length counts only the payload bytes and excludes the prefix itself.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Prefix {
+    /// A single byte length.
+    U8,
+    /// A two byte length.
+    U16(Endian),
+    /// A four byte length.
+    U32(Endian),
+    /// A variable length LEB128 encoded length, using 7 bits per byte.
+    Varint,
+}
+
+impl Prefix {
+    /// Gets the largest payload length that may be encoded.
+    pub fn max_len(self) -> usize {
+        match self {
+            Prefix::U8 => u8::MAX as usize,
+            Prefix::U16(_) => u16::MAX as usize,
+            Prefix::U32(_) => usize::try_from(u32::MAX).unwrap_or(usize::MAX),
+            Prefix::Varint => usize::MAX,
+        }
+    }
+
+    /// Gets the number of bytes needed to encode a payload length.
+    pub fn size(self, len: usize) -> usize {
+        match self {
+            Prefix::U8 => 1,
+            Prefix::U16(_) => 2,
+            Prefix::U32(_) => 4,
+            Prefix::Varint => {
+                let bits = usize::BITS - len.leading_zeros();
+                (bits.max(1) as usize).div_ceil(7)
+            }
+        }
+    }
+
+    /// Writes the prefix for `len` into the start of `dst`.
+    fn encode(self, len: usize, dst: &mut [u8]) -> usize {
+        match self {
+            Prefix::U8 => dst[0] = len as u8,
+            Prefix::U16(Endian::Big) => dst[..2].copy_from_slice(&(len as u16).to_be_bytes()),
+            Prefix::U16(Endian::Little) => dst[..2].copy_from_slice(&(len as u16).to_le_bytes()),
+            Prefix::U32(Endian::Big) => dst[..4].copy_from_slice(&(len as u32).to_be_bytes()),
+            Prefix::U32(Endian::Little) => dst[..4].copy_from_slice(&(len as u32).to_le_bytes()),
+            Prefix::Varint => {
+                let mut val = len;
+                let mut n = 0;
+                while val >= 0x80 {
+                    dst[n] = (val as u8) | 0x80;
+                    val >>= 7;
+                    n += 1;
+                }
+                dst[n] = val as u8;
+            }
+        }
+        self.size(len)
+    }
+
+    /// Reads a prefix from the start of `src`.
+    ///
+    /// Returns the size of the prefix and the payload length, or `None` if
+    /// more bytes are needed.
+    fn decode(self, src: &[u8]) -> io::Result<Option<(usize, usize)>> {
+        let fixed = |n: usize| src.get(..n);
+        Ok(match self {
+            Prefix::U8 => fixed(1).map(|b| (1, b[0] as usize)),
+            Prefix::U16(e) => fixed(2).map(|b| {
+                let b = [b[0], b[1]];
+                let v = match e {
+                    Endian::Big => u16::from_be_bytes(b),
+                    Endian::Little => u16::from_le_bytes(b),
+                };
+                (2, v as usize)
+            }),
+            Prefix::U32(e) => match fixed(4) {
+                None => None,
+                Some(b) => {
+                    let b = [b[0], b[1], b[2], b[3]];
+                    let v = match e {
+                        Endian::Big => u32::from_be_bytes(b),
+                        Endian::Little => u32::from_le_bytes(b),
+                    };
+                    Some((4, usize::try_from(v).map_err(|_| too_long())?))
+                }
+            },
+            Prefix::Varint => {
+                let mut val: usize = 0;
+                let mut found = None;
+                for (i, &b) in src.iter().enumerate() {
+                    let shift = 7 * i as u32;
+                    let bits = (b & 0x7f) as usize;
+                    if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                        return Err(too_long());
+                    }
+                    val |= bits << shift;
+                    if b & 0x80 == 0 {
+                        found = Some((i + 1, val));
+                        break;
+                    }
+                }
+                found
+            }
+        })
+    }
+}
+
+fn too_long() -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, "frame length is too large")
+}
+
+/// Reads length-delimited frames from a [`BufReader`].
+///
+/// Each frame is returned as a slice into the reader's [`Ring`], and it is
+/// consumed on the following call to [`.next_frame()`]. An entire frame,
+/// including its prefix, must fit within the buffer capacity.
+///
+/// [`BufReader`]: ../struct.BufReader.html
+/// [`Ring`]: ../struct.Ring.html
+/// [`.next_frame()`]: #method.next_frame
+pub struct FrameReader<R> {
+    inner: BufReader<R>,
+    prefix: Prefix,
+    pending: usize,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Creates a new `FrameReader` using the given length prefix encoding.
+    pub fn new(inner: BufReader<R>, prefix: Prefix) -> Self {
+        Self {
+            inner,
+            prefix,
+            pending: 0,
+        }
+    }
+
+    /// Gets the length prefix encoding.
+    #[inline]
+    pub fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &BufReader<R> {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// The most recently returned frame is consumed first.
+    pub fn get_mut(&mut self) -> &mut BufReader<R> {
+        self.consume_pending();
+        &mut self.inner
+    }
+
+    /// Unwraps this `FrameReader`, returning the underlying reader.
+    ///
+    /// The most recently returned frame is consumed first.
+    pub fn into_inner(mut self) -> BufReader<R> {
+        self.consume_pending();
+        self.inner
+    }
+
+    /// Reads the next frame.
+    ///
+    /// Returns `None` when the end of the stream is reached on a frame
+    /// boundary. If the stream ends within a frame, an error with a kind of
+    /// `UnexpectedEof` is returned. A frame that cannot fit within the buffer
+    /// results in an error with a kind of `InvalidData`.
+    pub fn next_frame(&mut self) -> io::Result<Option<&[u8]>> {
+        self.consume_pending();
+
+        let mut want = 1;
+        let (hdr, len) = loop {
+            let buf = self.inner.fill_at_least(want)?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match self.prefix.decode(buf)? {
+                Some(v) => break v,
+                None if buf.len() < want => return Err(ErrorKind::UnexpectedEof.into()),
+                None => want = buf.len() + 1,
+            }
+        };
+
+        let total = hdr.checked_add(len).ok_or_else(too_long)?;
+        if total > self.inner.capacity() {
+            return Err(too_long());
+        }
+        let buf = self.inner.fill_at_least(total)?;
+        if buf.len() < total {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        self.pending = total;
+        Ok(Some(&buf[hdr..total]))
+    }
+
+    fn consume_pending(&mut self) {
+        self.inner.consume(self.pending);
+        self.pending = 0;
+    }
+}
+
+/// Writes length-delimited frames to a [`BufWriter`].
+///
+/// [`BufWriter`]: ../struct.BufWriter.html
+pub struct FrameWriter<W: Write> {
+    inner: BufWriter<W>,
+    prefix: Prefix,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Creates a new `FrameWriter` using the given length prefix encoding.
+    pub fn new(inner: BufWriter<W>, prefix: Prefix) -> Self {
+        Self { inner, prefix }
+    }
+
+    /// Gets the length prefix encoding.
+    #[inline]
+    pub fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &BufWriter<W> {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut BufWriter<W> {
+        &mut self.inner
+    }
+
+    /// Unwraps this `FrameWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> BufWriter<W> {
+        self.inner
+    }
+
+    /// Writes a frame containing all of `data`.
+    ///
+    /// The frame may be larger than the buffer capacity. An error with a kind
+    /// of `InvalidInput` is returned if the length cannot be encoded by the
+    /// prefix.
+    pub fn write_frame(&mut self, data: &[u8]) -> io::Result<()> {
+        self.check_len(data.len())?;
+        let mut hdr = [0u8; 10];
+        let n = self.prefix.encode(data.len(), &mut hdr);
+        self.inner.write_all(&hdr[..n])?;
+        self.inner.write_all(data)
+    }
+
+    /// Writes a frame by encoding the payload directly into the buffer.
+    ///
+    /// Space is reserved for the prefix and up to `max_len` payload bytes,
+    /// flushing the buffer if needed. The function is given the payload slice
+    /// and returns the number of bytes it wrote, after which the prefix is
+    /// back-filled. For a [`Prefix::Varint`] that ends up shorter than was
+    /// reserved, the payload is shifted down to follow the prefix.
+    ///
+    /// An error with a kind of `InvalidInput` is returned if the reservation
+    /// exceeds the buffer capacity, or if the function reports writing more
+    /// than `max_len` bytes.
+    ///
+    /// [`Prefix::Varint`]: enum.Prefix.html#variant.Varint
+    pub fn write_frame_with<F>(&mut self, max_len: usize, f: F) -> io::Result<usize>
+    where
+        F: FnOnce(&mut [u8]) -> io::Result<usize>,
+    {
+        self.check_len(max_len)?;
+        let prefix = self.prefix;
+        let hdr = prefix.size(max_len);
+        let total = hdr.checked_add(max_len).ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "length exceeds buffer capacity")
+        })?;
+        let dst = &mut self.inner.reserve(total)?[..total];
+        let len = f(&mut dst[hdr..])?;
+        if len > max_len {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "frame length exceeds reservation",
+            ));
+        }
+        let used = prefix.size(len);
+        if used < hdr {
+            dst.copy_within(hdr..hdr + len, used);
+        }
+        prefix.encode(len, dst);
+        self.inner.commit(used + len);
+        Ok(len)
+    }
+
+    /// Flushes the buffer and the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn check_len(&self, len: usize) -> io::Result<()> {
+        if len > self.prefix.max_len() {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "frame length exceeds prefix range",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use super::{BufWriter, IntoInnerError, Ring, WriterPanicked};
+use crate::Result;
+
+use std::io::{self, Write};
+
+/// The `LineWriter` adds line buffering to any writer using a specialized
+/// buffer.
+///
+/// This is very similar to `std::io::LineWriter`, but it uses a [`BufWriter`]
+/// with a [`Ring`] for the internal buffer. Each write is flushed up to and
+/// including its last newline, and any partial line that follows is kept in
+/// the buffer until a later write completes it, the buffer fills, or the
+/// writer is flushed or dropped.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::LineWriter;
+/// use std::io::Write;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut wr = LineWriter::new(Vec::new(), 4000)?;
+/// wr.write_all(b"hello\nwor")?;
+/// assert_eq!(wr.get_ref(), b"hello\n");
+/// wr.write_all(b"ld\n")?;
+/// assert_eq!(wr.get_ref(), b"hello\nworld\n");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`BufWriter`]: struct.BufWriter.html
+/// [`Ring`]: struct.Ring.html
+pub struct LineWriter<W: Write> {
+    inner: BufWriter<W>,
+}
+
+impl<W: Write> LineWriter<W> {
+    /// Creates a new `LineWriter`.
+    pub fn new(inner: W, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            inner: BufWriter::new(inner, capacity)?,
+        })
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// Writing directly to the underlying writer may cause data to be
+    /// observed out of order with the buffered partial line.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    /// Unwraps this `LineWriter`, returning the underlying writer.
+    ///
+    /// The buffer is flushed first. On `Err`, the [`IntoInnerError`] holds
+    /// the [`BufWriter`] for recovering the unwritten data, just as with
+    /// [`BufWriter::into_inner()`].
+    ///
+    /// [`BufWriter`]: struct.BufWriter.html
+    /// [`BufWriter::into_inner()`]: struct.BufWriter.html#method.into_inner
+    /// [`IntoInnerError`]: struct.IntoInnerError.html
+    pub fn into_inner(self) -> std::result::Result<W, IntoInnerError<W>> {
+        self.inner.into_inner()
+    }
+
+    /// Disassembles this `LineWriter` into the underlying writer and the
+    /// [`Ring`] used for buffering, containing any buffered but unwritten
+    /// data.
+    ///
+    /// See [`BufWriter::into_parts()`] for details.
+    ///
+    /// [`BufWriter::into_parts()`]: struct.BufWriter.html#method.into_parts
+    /// [`Ring`]: struct.Ring.html
+    pub fn into_parts(self) -> (W, std::result::Result<Ring, WriterPanicked>) {
+        self.inner.into_parts()
+    }
+}
+
+impl<W: Write> Write for LineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let end = match buf.iter().rposition(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None => {
+                // Write out a line that was completed by a previous write
+                // before buffering more partial data.
+                if self.inner.buffer().last() == Some(&b'\n') {
+                    self.inner.flush_buf()?;
+                }
+                return self.inner.write(buf);
+            }
+        };
+
+        self.inner.flush_buf()?;
+        let n = self.inner.write_inner(&buf[..end])?;
+        if n == 0 {
+            return Ok(0);
+        }
+
+        // Buffer what remains, but only up to the last newline that fits if
+        // the complete lines were not all written.
+        let tail = if n >= end {
+            &buf[n..]
+        } else if end - n <= self.inner.capacity() {
+            &buf[n..end]
+        } else {
+            let scan = &buf[n..n + self.inner.capacity()];
+            match scan.iter().rposition(|&b| b == b'\n') {
+                Some(i) => &scan[..i + 1],
+                None => scan,
+            }
+        };
+        Ok(n + self.inner.write_to_buf(tail))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
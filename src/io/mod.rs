@@ -7,13 +7,45 @@
 //! the [`Ring`] may only written to as readable space is consumed, whereas
 //! the [`InfiniteRing`] is always writable and will overwrite unconsumed
 //! space as needed.
+//!
+//! A [`Ring`] may also be split into a [`Producer`] and a [`Consumer`] that
+//! share the buffer between two threads without locking. When the threads
+//! need to wait on each other, a [`pipe()`] provides blocking [`PipeReader`]
+//! and [`PipeWriter`] halves over the same buffer. On Unix targets, the
+//! [`SharedRing`] shares a buffer between processes, and the [`FileRing`]
+//! persists the buffer in a file. The [`framed`] module reads and writes
+//! length-delimited frames over these buffers without copying.
 
 mod ring;
 pub use self::ring::*;
 
+mod split;
+pub use self::split::{Consumer, Producer};
+
+mod pipe;
+pub use self::pipe::{pipe, PipeReader, PipeWriter};
+
+#[cfg(unix)]
+mod shared;
+#[cfg(unix)]
+pub use self::shared::{FileRing, SharedRing};
+
 mod buffer;
 pub use self::buffer::*;
 
+mod stream;
+pub use self::stream::BufStream;
+
+mod line;
+pub use self::line::LineWriter;
+
+pub mod framed;
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod async_buf;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub use self::async_buf::{AsyncBufReader, AsyncBufWriter};
+
 use std::cmp;
 use std::io::{self, BufRead};
 use std::slice;
@@ -114,9 +146,12 @@ pub trait SeqWrite {
 mod tests {
     use crate::os;
 
-    use super::super::Size;
+    use super::super::{Operation, Size};
     use super::{InfiniteRing, Ring, SeqRead, SeqWrite};
+    use std::io::Read;
     use std::io::{BufRead, Write};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
 
     #[test]
     fn size() {
@@ -133,6 +168,27 @@ mod tests {
         assert_eq!(buf.write_len(), sz.size(1) - 4);
     }
 
+    #[test]
+    fn size_huge() {
+        let sz = Size::huge().unwrap_or_default();
+        let mut buf = Ring::with_size(1000, sz).expect("failed to create buffer");
+        assert_eq!(buf.write_capacity(), sz.size(1));
+
+        let off = buf.write_capacity() - 2;
+        buf.feed(off);
+        buf.consume(off);
+        buf.write_all(b"test").expect("failed to write all bytes");
+        assert_eq!(buf.as_read_slice(4), b"test");
+    }
+
+    #[test]
+    fn size_unaligned() {
+        let sz = unsafe { Size::with_size(Size::alloc().size(1) / 2) };
+        let err = Ring::with_size(1000, sz).expect_err("created unaligned ring");
+        assert_eq!(err.operation(), Operation::RingAllocate);
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
     #[test]
     fn wrap() {
         let mut buf = Ring::new(1000).expect("failed to create ring buffer");
@@ -224,4 +280,922 @@ mod tests {
         let end = bytes.len() - (ring.write_capacity() % bytes.len());
         assert_eq!(ring.as_read_slice(10), &cmp[end..(end + 10)]);
     }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Ring>();
+        assert_send_sync::<InfiniteRing>();
+    }
+
+    #[test]
+    fn send_ring() {
+        let ring = Ring::new(1000).expect("failed to create ring");
+        let bytes = b"anthropomorphologically";
+        let n = ring.write_capacity() / bytes.len();
+
+        let mut ring = thread::spawn(move || {
+            let mut ring = ring;
+            for _ in 0..n {
+                ring.write_all(bytes).expect("failed to write");
+            }
+            ring
+        })
+        .join()
+        .expect("thread panicked");
+
+        assert_eq!(ring.read_len(), n * bytes.len());
+        for _ in 0..n {
+            assert_eq!(ring.as_read_slice(bytes.len()), &bytes[..]);
+            ring.consume(bytes.len());
+        }
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn share_ring() {
+        let ring = InfiniteRing::new(1000).expect("failed to create ring");
+        let n = ring.write_capacity() / 8;
+        let ring = Arc::new(Mutex::new(ring));
+
+        let handles = (0..4u8)
+            .map(|i| {
+                let ring = Arc::clone(&ring);
+                thread::spawn(move || {
+                    for _ in 0..n {
+                        ring.lock().unwrap().write_all(&[b'a' + i; 8]).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for h in handles {
+            h.join().expect("thread panicked");
+        }
+
+        let ring = ring.lock().unwrap();
+        assert_eq!(ring.read_len(), ring.write_capacity());
+        for chunk in ring.as_read_slice(usize::MAX).chunks(8) {
+            assert!(chunk.iter().all(|&b| b == chunk[0]));
+        }
+    }
+
+    #[test]
+    fn split() {
+        let ring = Ring::new(1000).expect("failed to create ring");
+        let cap = ring.write_capacity();
+        let (mut tx, mut rx) = ring.split();
+        assert!(!tx.is_closed());
+        assert_eq!(tx.write_len(), cap);
+
+        // pick some bytes that won't fit evenly in the capacity
+        let bytes = b"anthropomorphologically";
+        let n = 10 * cap / bytes.len();
+
+        let writer = thread::spawn(move || {
+            for _ in 0..n {
+                let mut rem = &bytes[..];
+                while !rem.is_empty() {
+                    let len = tx.write(rem).expect("failed to write");
+                    rem = &rem[len..];
+                    if len == 0 {
+                        thread::yield_now();
+                    }
+                }
+            }
+        });
+
+        let mut word = [0u8; 23];
+        for _ in 0..n {
+            while rx.read_len() < bytes.len() {
+                thread::yield_now();
+            }
+            assert_eq!(rx.as_read_slice(bytes.len()), &bytes[..]);
+            rx.read_exact(&mut word).expect("failed to read");
+            assert_eq!(&word, bytes);
+        }
+
+        writer.join().expect("thread panicked");
+        assert!(rx.is_closed());
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn pipe() {
+        let (mut rx, mut tx) = super::pipe(1000).expect("failed to create pipe");
+        assert!(!rx.is_closed());
+        let count = tx.write_capacity();
+
+        let writer = thread::spawn(move || {
+            for i in 0..count {
+                tx.write_all(&(i as u64).to_le_bytes())
+                    .expect("failed to write");
+            }
+        });
+
+        let mut buf = [0u8; 8];
+        for i in 0..count {
+            rx.read_exact(&mut buf).expect("failed to read");
+            assert_eq!(u64::from_le_bytes(buf), i as u64);
+        }
+        writer.join().expect("thread panicked");
+
+        assert!(rx.is_closed());
+        assert_eq!(rx.read(&mut buf).expect("failed to read"), 0);
+    }
+
+    #[test]
+    fn pipe_timeout() {
+        use std::time::Duration;
+
+        let (mut rx, mut tx) = super::pipe(1000).expect("failed to create pipe");
+        rx.set_read_timeout(Some(Duration::from_millis(10)));
+        tx.set_write_timeout(Some(Duration::from_millis(10)));
+
+        let mut buf = [0u8; 8];
+        let err = rx.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let fill = vec![0u8; tx.write_capacity()];
+        tx.write_all(&fill).expect("failed to write");
+        let err = tx.write(b"more").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        rx.consume(4);
+        assert_eq!(tx.write(b"more").expect("failed to write"), 4);
+
+        drop(rx);
+        assert!(tx.is_closed());
+        let err = tx.write(b"more").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn buf_reader_peek() {
+        use super::BufReader;
+
+        // Yields at most 3 bytes per read.
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(self.0.len()).min(3);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let src = Trickle(b"hello world");
+        let mut rd = BufReader::new(src, 1000).expect("failed to create buffer");
+        assert_eq!(rd.fill_buf().expect("failed to fill"), b"hel");
+        assert_eq!(rd.peek(8).expect("failed to peek"), b"hello wo");
+        let buf = rd.fill_at_least(5).expect("failed to fill");
+        assert!(buf.starts_with(b"hello wo"));
+        rd.consume(6);
+        assert_eq!(rd.peek(100).expect("failed to peek"), b"world");
+
+        let err = rd.peek(Size::alloc().size(1) + 1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn buf_writer_exact_fill() {
+        use super::BufWriter;
+
+        let mut wr = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        wr.write_all(b"head").expect("failed to write");
+        let fill = vec![1u8; Size::alloc().size(1) - 4];
+        wr.write_all(&fill).expect("failed to write");
+        let data = wr.into_inner().map_err(|e| e.into_error());
+        let data = data.expect("failed to flush");
+        assert_eq!(&data[..4], b"head");
+        assert_eq!(&data[4..], &fill[..]);
+    }
+
+    #[test]
+    fn buf_writer_reserve() {
+        use super::BufWriter;
+
+        let cap = Size::alloc().size(1);
+        let mut wr = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        let err = wr.reserve(cap + 1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let buf = wr.reserve(cap - 10).expect("failed to reserve");
+        assert_eq!(buf.len(), cap);
+        buf[..cap - 10].fill(1);
+        wr.commit(cap - 10);
+        assert!(wr.get_ref().is_empty());
+
+        // Not enough space remains, so the buffered bytes are flushed.
+        let buf = wr.reserve(20).expect("failed to reserve");
+        assert_eq!(buf.len(), cap);
+        buf[..5].copy_from_slice(b"hello");
+        wr.commit(5);
+        assert_eq!(wr.get_ref().len(), cap - 10);
+
+        wr.flush().expect("failed to flush");
+        assert_eq!(&wr.get_ref()[cap - 10..], b"hello");
+    }
+
+    #[test]
+    fn buf_reader_seek() {
+        use super::BufReader;
+        use std::io::{Cursor, Seek, SeekFrom};
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let src = Cursor::new(&data[..]);
+        let mut rd = BufReader::new(src, 1000).expect("failed to create buffer");
+        let cap = Size::alloc().size(1) as u64;
+
+        assert_eq!(rd.fill_buf().expect("failed to fill").len() as u64, cap);
+        assert_eq!(rd.stream_position().expect("failed to seek"), 0);
+
+        // Stays within the buffered window, so nothing is discarded.
+        assert_eq!(rd.seek(SeekFrom::Current(10)).expect("failed to seek"), 10);
+        assert_eq!(rd.buffer().len() as u64, cap - 10);
+        assert_eq!(rd.buffer()[0], 10);
+        rd.seek_relative(5).expect("failed to seek");
+        assert_eq!(rd.buffer().len() as u64, cap - 15);
+        assert_eq!(rd.get_ref().position(), cap);
+
+        // Seeking backwards or past the window discards the buffer.
+        assert_eq!(rd.seek(SeekFrom::Current(-5)).expect("failed to seek"), 10);
+        assert!(rd.buffer().is_empty());
+        assert_eq!(rd.fill_buf().expect("failed to fill")[0], 10);
+        let off = SeekFrom::Current(cap as i64 + 1);
+        assert_eq!(rd.seek(off).expect("failed to seek"), cap + 11);
+        assert!(rd.buffer().is_empty());
+        assert_eq!(rd.fill_buf().expect("failed to fill")[0], (cap + 11) as u8);
+
+        let pos = rd.seek(SeekFrom::End(-1)).expect("failed to seek");
+        assert_eq!(pos, 9_999);
+        assert_eq!(rd.fill_buf().expect("failed to fill"), &[data[9_999]]);
+        rd.seek(SeekFrom::Start(3)).expect("failed to seek");
+        assert_eq!(rd.fill_buf().expect("failed to fill")[0], 3);
+    }
+
+    #[test]
+    fn buf_writer_seek() {
+        use super::BufWriter;
+        use std::io::{Cursor, Seek, SeekFrom};
+
+        let dst = Cursor::new(vec![0u8; 8]);
+        let mut wr = BufWriter::new(dst, 1000).expect("failed to create buffer");
+        wr.write_all(b"hello").expect("failed to write");
+        assert_eq!(wr.seek(SeekFrom::Start(1)).expect("failed to seek"), 1);
+        assert_eq!(wr.get_ref().get_ref(), b"hello\0\0\0");
+        wr.write_all(b"ipp").expect("failed to write");
+        wr.flush().expect("failed to flush");
+        assert_eq!(wr.get_ref().get_ref(), b"hippo\0\0\0");
+    }
+
+    #[test]
+    fn buf_stream() {
+        use super::BufStream;
+        use std::io::Cursor;
+
+        struct Duplex(Cursor<Vec<u8>>, Vec<u8>);
+        impl Read for Duplex {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.0.read(buf)
+            }
+        }
+        impl Write for Duplex {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.1.write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let src = Cursor::new(b"hello\nworld\n".to_vec());
+        let inner = Duplex(src, Vec::new());
+        let mut stream = BufStream::new(inner, 1000).expect("failed to create buffer");
+        let mut line = String::new();
+        stream.read_line(&mut line).expect("failed to read");
+        assert_eq!(line, "hello\n");
+        assert_eq!(stream.buffer(), b"world\n");
+
+        stream.write_all(b"ping").expect("failed to write");
+        assert!(stream.get_ref().1.is_empty());
+        stream.flush().expect("failed to flush");
+        assert_eq!(stream.get_ref().1, b"ping");
+
+        stream.write_all(b"pong").expect("failed to write");
+        let (inner, rd, wr) = stream.into_parts();
+        assert_eq!(inner.1, b"ping");
+        assert_eq!(rd.as_read_slice(usize::MAX), b"world\n");
+        let wr = wr.map_err(|_| "writer panicked").unwrap();
+        assert_eq!(wr.as_read_slice(usize::MAX), b"pong");
+    }
+
+    #[test]
+    fn line_writer() {
+        use super::LineWriter;
+
+        let mut wr = LineWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        wr.write_all(b"partial").expect("failed to write");
+        assert!(wr.get_ref().is_empty());
+        wr.write_all(b" line\nmore").expect("failed to write");
+        assert_eq!(wr.get_ref(), b"partial line\n");
+        wr.write_all(b"\n").expect("failed to write");
+        assert_eq!(wr.get_ref(), b"partial line\nmore\n");
+        wr.write_all(b"a\nb\nc").expect("failed to write");
+        assert_eq!(wr.get_ref(), b"partial line\nmore\na\nb\n");
+
+        let (inner, ring) = wr.into_parts();
+        let ring = ring.map_err(|_| "writer panicked").unwrap();
+        assert_eq!(ring.as_read_slice(usize::MAX), b"c");
+        assert_eq!(inner, b"partial line\nmore\na\nb\n");
+
+        let mut wr = LineWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        wr.write_all(b"done").expect("failed to write");
+        let inner = wr.into_inner().map_err(|e| e.into_error());
+        assert_eq!(inner.expect("failed to flush"), b"done");
+    }
+
+    #[test]
+    fn buf_reader_next_line() {
+        use super::BufReader;
+
+        let data: Vec<u8> = (0..1000)
+            .flat_map(|i| format!("this is test line {}\r\n", i).into_bytes())
+            .chain(b"last".iter().copied())
+            .collect();
+        let mut rd = BufReader::new(&data[..], 1000).expect("failed to create buffer");
+        for i in 0..1000 {
+            let line = rd.next_line().expect("failed to read");
+            assert_eq!(line, Some(format!("this is test line {}", i).as_bytes()));
+        }
+        assert_eq!(rd.next_line().expect("failed to read"), Some(&b"last"[..]));
+        assert_eq!(rd.next_line().expect("failed to read"), None);
+
+        let mut rd = BufReader::new(&b"a,bc,,d"[..], 1000).expect("failed to create buffer");
+        for field in [&b"a,"[..], b"bc,", b",", b"d"] {
+            assert_eq!(rd.next_until(b',').expect("failed to read"), Some(field));
+        }
+        assert_eq!(rd.next_until(b',').expect("failed to read"), None);
+
+        let long = vec![b'x'; Size::alloc().size(1) + 1];
+        let mut rd = BufReader::new(&long[..], 1000).expect("failed to create buffer");
+        let err = rd.next_line().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn ring_bytes() {
+        use bytes::{Buf, BufMut};
+
+        let mut ring = Ring::new(1000).expect("failed to create ring");
+        let cap = ring.write_capacity();
+        ring.put_u32(0xdead_beef);
+        ring.put_slice(b"hello");
+        assert_eq!(ring.remaining_mut(), cap - 9);
+        assert_eq!(ring.chunk_mut().len(), cap - 9);
+        assert_eq!(ring.get_u32(), 0xdead_beef);
+        assert_eq!(ring.chunk(), b"hello");
+
+        // Wrap around the end of the mapping.
+        ring.advance(5);
+        ring.put_bytes(7, cap - 4);
+        ring.put_u32_le(42);
+        assert_eq!(ring.remaining(), cap);
+        assert_eq!(ring.chunk().len(), cap);
+        ring.advance(cap - 4);
+        assert_eq!(ring.get_u32_le(), 42);
+
+        let mut ring = InfiniteRing::new(1000).expect("failed to create ring");
+        ring.write_all(b"hello world").expect("failed to write");
+        assert_eq!(Buf::remaining(&ring), 11);
+        Buf::advance(&mut ring, 6);
+        assert_eq!(Buf::chunk(&ring), b"world");
+    }
+
+    #[test]
+    fn framed() {
+        use super::framed::{Endian, FrameReader, FrameWriter, Prefix};
+        use super::{BufReader, BufWriter};
+
+        let prefixes = [
+            Prefix::U8,
+            Prefix::U16(Endian::Big),
+            Prefix::U16(Endian::Little),
+            Prefix::U32(Endian::Big),
+            Prefix::U32(Endian::Little),
+            Prefix::Varint,
+        ];
+        for &prefix in prefixes.iter() {
+            let buf = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+            let mut wr = FrameWriter::new(buf, prefix);
+            for i in 0..2000 {
+                let len = i % 200;
+                let data = vec![i as u8; len];
+                wr.write_frame(&data).expect("failed to write");
+                let n = wr
+                    .write_frame_with(200, |buf| {
+                        buf[..len].fill(!i as u8);
+                        Ok(len)
+                    })
+                    .expect("failed to write");
+                assert_eq!(n, len);
+            }
+            let data = wr.into_inner().into_inner();
+            let data = data.map_err(|e| e.into_error()).expect("failed to flush");
+
+            let buf = BufReader::new(&data[..], 1000).expect("failed to create buffer");
+            let mut rd = FrameReader::new(buf, prefix);
+            for i in 0..2000 {
+                let len = i % 200;
+                let frame = rd.next_frame().expect("failed to read");
+                assert_eq!(frame, Some(&vec![i as u8; len][..]));
+                let frame = rd.next_frame().expect("failed to read");
+                assert_eq!(frame, Some(&vec![!i as u8; len][..]));
+            }
+            assert_eq!(rd.next_frame().expect("failed to read"), None);
+
+            let end = data.len() - 1;
+            let buf = BufReader::new(&data[..end], 1000).expect("failed to create buffer");
+            let mut rd = FrameReader::new(buf, prefix);
+            let err = loop {
+                match rd.next_frame() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => panic!("truncated frame was not detected"),
+                    Err(e) => break e,
+                }
+            };
+            assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn framed_limits() {
+        use super::framed::{Endian, FrameReader, FrameWriter, Prefix};
+        use super::{BufReader, BufWriter};
+        use std::io::ErrorKind;
+
+        assert_eq!(Prefix::Varint.size(0), 1);
+        assert_eq!(Prefix::Varint.size(127), 1);
+        assert_eq!(Prefix::Varint.size(128), 2);
+        assert_eq!(Prefix::Varint.size(usize::MAX), 10);
+
+        let buf = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        let mut wr = FrameWriter::new(buf, Prefix::U8);
+        let err = wr.write_frame(&[0; 256]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = wr.write_frame_with(10, |_| Ok(11)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let buf = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        let mut wr = FrameWriter::new(buf, Prefix::Varint);
+        let err = wr.write_frame_with(usize::MAX, |_| Ok(0)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let cap = Size::alloc().size(1);
+        let buf = BufWriter::new(Vec::new(), 1000).expect("failed to create buffer");
+        let mut wr = FrameWriter::new(buf, Prefix::U32(Endian::Big));
+        let err = wr.write_frame_with(cap, |_| Ok(0)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        wr.write_frame(&vec![1; cap]).expect("failed to write");
+        let data = wr.into_inner().into_inner();
+        let data = data.map_err(|e| e.into_error()).expect("failed to flush");
+        assert_eq!(data.len(), cap + 4);
+
+        let buf = BufReader::new(&data[..], 1000).expect("failed to create buffer");
+        let mut rd = FrameReader::new(buf, Prefix::U32(Endian::Big));
+        let err = rd.next_frame().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let data = [0xff; 11];
+        let buf = BufReader::new(&data[..], 1000).expect("failed to create buffer");
+        let mut rd = FrameReader::new(buf, Prefix::Varint);
+        let err = rd.next_frame().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_buf_reader() {
+        use super::AsyncBufReader;
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+        let (mut cli, srv) = tokio::io::duplex(64);
+        let mut rd = AsyncBufReader::new(srv, 1000).expect("failed to create buffer");
+
+        let send = async move {
+            for i in 0..1000 {
+                let line = format!("this is test line {}\n", i);
+                cli.write_all(line.as_bytes()).await.unwrap();
+            }
+        };
+        let recv = async {
+            let mut line = String::new();
+            rd.read_line(&mut line).await.unwrap();
+            assert_eq!(line, "this is test line 0\n");
+            let mut rest = String::new();
+            rd.read_to_string(&mut rest).await.unwrap();
+            assert!(rest.ends_with("this is test line 999\n"));
+        };
+        tokio::join!(send, recv);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_buf_writer() {
+        use super::AsyncBufWriter;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (send, mut recv) = tokio::io::duplex(64);
+        let mut wr = AsyncBufWriter::new(send, 1000).expect("failed to create buffer");
+
+        // The duplex is smaller than the write, so the ring absorbs it.
+        wr.write_all(&[1u8; 1000]).await.unwrap();
+        assert_eq!(wr.buffer().len(), 1000);
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let write = async {
+            wr.write_all(&data).await.unwrap();
+            wr.shutdown().await.unwrap();
+        };
+        let read = async {
+            let mut out = Vec::new();
+            recv.read_to_end(&mut out).await.unwrap();
+            out
+        };
+        let ((), out) = tokio::join!(write, read);
+        assert_eq!(out.len(), 101_000);
+        assert!(out[..1000].iter().all(|&b| b == 1));
+        assert_eq!(&out[1000..], &data[..]);
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn futures_buf_reader() {
+        use super::AsyncBufReader;
+        use futures::io::{AsyncBufReadExt, AsyncReadExt};
+
+        let src: Vec<u8> = (0..1000)
+            .flat_map(|i| format!("this is test line {}\n", i).into_bytes())
+            .collect();
+        futures::executor::block_on(async {
+            let mut rd = AsyncBufReader::new(&src[..], 1000).expect("failed to create buffer");
+            let mut line = String::new();
+            rd.read_line(&mut line).await.unwrap();
+            assert_eq!(line, "this is test line 0\n");
+
+            let ptr = rd.buffer().as_ptr();
+            let buf = rd.fill_buf().await.unwrap();
+            assert_eq!(buf.as_ptr(), ptr);
+            assert!(buf.starts_with(b"this is test line 1\n"));
+
+            let mut rest = String::new();
+            rd.read_to_string(&mut rest).await.unwrap();
+            assert!(rest.ends_with("this is test line 999\n"));
+        });
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn futures_buf_writer() {
+        use super::AsyncBufWriter;
+        use futures::io::{AsyncWrite, AsyncWriteExt};
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        // Accepts at most 100 bytes per write, and only on every other poll.
+        struct Throttle(Vec<u8>, bool);
+        impl AsyncWrite for Throttle {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.1 = !self.1;
+                if self.1 {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                let n = buf.len().min(100);
+                self.0.extend_from_slice(&buf[..n]);
+                Poll::Ready(Ok(n))
+            }
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        futures::executor::block_on(async {
+            let mut wr = AsyncBufWriter::new(Throttle(Vec::new(), false), 1000)
+                .expect("failed to create buffer");
+            wr.write_all(b"hello").await.unwrap();
+            assert_eq!(wr.buffer(), b"hello");
+            assert!(wr.get_ref().0.is_empty());
+
+            wr.write_all(&data).await.unwrap();
+            wr.close().await.unwrap();
+            let (inner, ring) = wr.into_parts();
+            assert!(ring.is_empty());
+            assert_eq!(&inner.0[..5], b"hello");
+            assert_eq!(&inner.0[5..], &data[..]);
+        });
+    }
+
+    #[test]
+    fn split_pending() {
+        let mut ring = Ring::new(1000).expect("failed to create ring");
+        ring.write_all(b"hello world").expect("failed to write");
+        ring.consume(6);
+
+        let (mut tx, mut rx) = ring.split();
+        assert_eq!(rx.fill_buf().expect("failed to fill"), b"world");
+        tx.write_all(b"!").expect("failed to write");
+        assert_eq!(rx.fill_buf().expect("failed to fill"), b"world!");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shared() {
+        use super::SharedRing;
+        use std::os::unix::net::UnixStream;
+
+        let (a, b) = UnixStream::pair().expect("failed to create socket pair");
+        let mut tx = SharedRing::new(1000).expect("failed to create ring");
+        tx.send(&a).expect("failed to send ring");
+        let mut rx = SharedRing::recv(&b).expect("failed to receive ring");
+        assert_eq!(rx.write_capacity(), tx.write_capacity());
+
+        // pick some bytes that won't fit evenly in the capacity
+        let bytes = b"anthropomorphologically";
+        let n = tx.write_capacity() / bytes.len();
+        for _ in 0..3 {
+            for _ in 0..n {
+                tx.write_all(bytes).expect("failed to write");
+            }
+            assert_eq!(rx.read_len(), n * bytes.len());
+            assert_eq!(tx.write_len(), tx.write_capacity() - n * bytes.len());
+            for _ in 0..n {
+                assert_eq!(rx.as_read_slice(bytes.len()), &bytes[..]);
+                rx.consume(bytes.len());
+            }
+            assert!(rx.is_empty());
+            assert_eq!(tx.write_len(), tx.write_capacity());
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shared_corrupt() {
+        use super::SharedRing;
+        use crate::{MapMut, SpanMut};
+
+        let mut tx = SharedRing::new(1000).expect("failed to create ring");
+        tx.write_all(b"hello").expect("failed to write");
+        let cap = tx.write_capacity() as u64;
+
+        // Modify the cursors through a separate mapping as a peer could.
+        let mut hdr = MapMut::with_options()
+            .len(Size::alloc().size(1))
+            .map_fd(&tx)
+            .expect("failed to map header");
+        let (rpos, wpos) = (24, 32);
+        for (r, w) in [(6, 5), (0, cap + 1), (u64::MAX, cap)] {
+            hdr.write_volatile(rpos, r);
+            hdr.write_volatile(wpos, w);
+            assert_eq!(tx.read_len(), 0);
+            assert_eq!(tx.write_len(), 0);
+            assert!(tx.as_read_slice(usize::MAX).is_empty());
+            assert_eq!(tx.write(b"world").expect("failed to write"), 0);
+            tx.consume(10);
+        }
+
+        hdr.write_volatile(rpos, 2u64);
+        hdr.write_volatile(wpos, 5u64);
+        assert_eq!(tx.as_read_slice(usize::MAX), b"llo");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn recv_fd() {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+        use std::time::Duration;
+        use std::{mem, ptr};
+
+        // Sends all descriptors in a single `SCM_RIGHTS` control message.
+        fn send_fds(sock: &UnixStream, fds: &[i32]) {
+            let mut data = [0u8; 1];
+            let mut ctl = [0u64; 64];
+            let size = mem::size_of_val(fds) as u32;
+            unsafe {
+                let mut iov = libc::iovec {
+                    iov_base: data.as_mut_ptr() as *mut libc::c_void,
+                    iov_len: data.len(),
+                };
+                let mut msg: libc::msghdr = mem::zeroed();
+                msg.msg_iov = &mut iov;
+                msg.msg_iovlen = 1;
+                msg.msg_control = ctl.as_mut_ptr() as *mut libc::c_void;
+                msg.msg_controllen = libc::CMSG_SPACE(size) as _;
+                let cmsg = libc::CMSG_FIRSTHDR(&msg);
+                (*cmsg).cmsg_level = libc::SOL_SOCKET;
+                (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+                (*cmsg).cmsg_len = libc::CMSG_LEN(size) as _;
+                let dst = libc::CMSG_DATA(cmsg) as *mut i32;
+                ptr::copy_nonoverlapping(fds.as_ptr(), dst, fds.len());
+                assert!(libc::sendmsg(sock.as_raw_fd(), &msg, 0) > 0);
+            }
+        }
+
+        let (a, b) = UnixStream::pair().expect("failed to create socket pair");
+        let (mut c, d) = UnixStream::pair().expect("failed to create socket pair");
+
+        send_fds(&a, &[c.as_raw_fd(), d.as_raw_fd()]);
+        let fd = os::recv_fd(&b).expect("failed to receive");
+        let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+        assert_ne!(flags & libc::FD_CLOEXEC, 0);
+
+        // The extra descriptor was closed, so dropping `d` closes the peer.
+        drop(d);
+        c.set_read_timeout(Some(Duration::from_secs(5)))
+            .expect("failed to set timeout");
+        assert_eq!(c.read(&mut [0u8; 1]).expect("failed to read"), 0);
+
+        send_fds(&a, &[fd.as_raw_fd(); 16]);
+        let err = os::recv_fd(&b).expect_err("received truncated message");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn shared_sealed() {
+        use super::SharedRing;
+        use crate::{Input, Seals};
+        use std::os::unix::net::UnixStream;
+
+        let (a, b) = UnixStream::pair().expect("failed to create socket pair");
+        let mut tx = SharedRing::new(1000).expect("failed to create ring");
+        let seals = Seals::SHRINK | Seals::GROW;
+
+        tx.send(&a).expect("failed to send");
+        let err = SharedRing::recv_sealed(&b, seals).expect_err("attached without seals");
+        assert_eq!(
+            err.to_string(),
+            format!("failed to open memory fd, {}", Input::MissingSeals)
+        );
+
+        tx.add_seals(seals).expect("failed to add seals");
+        tx.send(&a).expect("failed to send");
+        let mut rx = SharedRing::recv_sealed(&b, seals).expect("failed to receive");
+        let fd = tx.try_clone_fd().expect("failed to clone fd");
+        assert!(std::fs::File::from(fd).set_len(0).is_err());
+
+        tx.write_all(b"hello").expect("failed to write");
+        assert_eq!(rx.as_read_slice(5), b"hello");
+        rx.consume(5);
+        assert_eq!(tx.read_len(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shared_invalid() {
+        use super::SharedRing;
+        use crate::Input;
+
+        let tmp = tempdir::TempDir::new("vmap").expect("failed to create dir");
+        let path = tmp.path().join("shared_invalid");
+        std::fs::write(&path, vec![0u8; 2 * Size::alloc().size(1)]).expect("failed to write");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .expect("failed to open");
+
+        let err = SharedRing::from_fd(file.into()).expect_err("attached to invalid ring");
+        assert_eq!(
+            err.to_string(),
+            format!("failed to map file, {}", Input::InvalidHeader)
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_ring() {
+        use super::FileRing;
+        use crate::Flush;
+
+        let tmp = tempdir::TempDir::new("vmap").expect("failed to create dir");
+        let path = tmp.path().join("file_ring");
+
+        // pick some bytes that won't fit evenly in the capacity
+        let bytes = b"anthropomorphologically";
+        let cap = {
+            let mut ring = FileRing::open(&path, 1000).expect("failed to create ring");
+            let n = ring.write_capacity() / bytes.len();
+            for _ in 0..n {
+                ring.write_all(bytes).expect("failed to write");
+            }
+            ring.consume((n - 1) * bytes.len());
+            ring.write_all(bytes).expect("failed to write");
+            ring.flush(Flush::Sync).expect("failed to flush");
+            ring.write_capacity()
+        };
+
+        let mut ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.write_capacity(), cap);
+        assert_eq!(ring.read_len(), 2 * bytes.len());
+
+        let cmp = b"anthropomorphologicallyanthropomorphologically";
+        assert_eq!(ring.as_read_slice(cmp.len()), &cmp[..]);
+        ring.consume(bytes.len());
+        drop(ring);
+
+        let ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.as_read_slice(usize::MAX), &bytes[..]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_ring_recover() {
+        use super::FileRing;
+        use crate::Input;
+
+        let tmp = tempdir::TempDir::new("vmap").expect("failed to create dir");
+        let path = tmp.path().join("file_ring_recover");
+
+        // A crash after sizing the file leaves a zeroed header.
+        let sz = Size::alloc().size(1);
+        std::fs::write(&path, vec![0u8; 3 * sz]).expect("failed to write");
+        let mut ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.write_capacity(), 2 * sz);
+        assert!(ring.is_empty());
+        ring.write_all(b"hello").expect("failed to write");
+        drop(ring);
+
+        let ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.as_read_slice(usize::MAX), b"hello");
+        drop(ring);
+
+        let mut data = std::fs::read(&path).expect("failed to read");
+        data[0] = 1;
+        std::fs::write(&path, data).expect("failed to write");
+        let err = FileRing::open(&path, 0).expect_err("opened invalid ring");
+        assert_eq!(
+            err.to_string(),
+            format!("failed to map file, {}", Input::InvalidHeader)
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_ring_offset() {
+        use super::FileRing;
+        use crate::Input;
+
+        let tmp = tempdir::TempDir::new("vmap").expect("failed to create dir");
+        let path = tmp.path().join("file_ring_offset");
+
+        // A ring created on a system with a larger page size has its data
+        // further into the file than a new ring would.
+        let sz = Size::alloc().size(1);
+        let header = |off: usize, len: usize, wpos: u64| {
+            let mut data = vec![0u8; off + len];
+            let magic = u64::from_be_bytes(*b"vmapring");
+            let words = [magic, off as u64, len as u64, 0, wpos];
+            for (i, word) in words.iter().enumerate() {
+                data[i * 8..i * 8 + 8].copy_from_slice(&word.to_ne_bytes());
+            }
+            data
+        };
+        let mut data = header(4 * sz, 2 * sz, 5);
+        data[4 * sz..4 * sz + 5].copy_from_slice(b"hello");
+        std::fs::write(&path, data).expect("failed to write");
+
+        let mut ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.write_capacity(), 2 * sz);
+        assert_eq!(ring.as_read_slice(usize::MAX), b"hello");
+        ring.consume(5);
+        ring.write_all(b"world").expect("failed to write");
+        drop(ring);
+
+        let ring = FileRing::open(&path, 0).expect("failed to open ring");
+        assert_eq!(ring.as_read_slice(usize::MAX), b"world");
+        drop(ring);
+
+        for (off, len) in [(sz + 1, 2 * sz - 1), (0, 3 * sz), (sz, sz)] {
+            let mut data = header(off, len, 0);
+            data.resize(3 * sz, 0);
+            std::fs::write(&path, data).expect("failed to write");
+            let err = FileRing::open(&path, 0).expect_err("opened invalid ring");
+            assert_eq!(
+                err.to_string(),
+                format!("failed to map file, {}", Input::InvalidHeader)
+            );
+        }
+    }
 }
@@ -0,0 +1,293 @@
+use super::{Consumer, Producer, Ring, SeqRead, SeqWrite};
+use crate::Result;
+
+use std::io::{self, BufRead, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Creates a blocking in-process pipe backed by a [`Ring`].
+///
+/// The capacity is a minimum size for the buffer, and it is rounded up in the
+/// same way as [`Ring::new()`]. The returned [`PipeReader`] blocks while the
+/// pipe is empty, and the [`PipeWriter`] blocks while the pipe is full. Each
+/// half detects when the other half has been dropped. Just as with the
+/// [`Ring`], the entire readable range is always addressable as a single
+/// contiguous slice.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{BufRead, Read, Write};
+/// use std::thread;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (mut rx, mut tx) = vmap::io::pipe(4000)?;
+///
+/// let writer = thread::spawn(move || {
+///     for i in 0..1000 {
+///         writeln!(tx, "this is test line {}", i).unwrap();
+///     }
+/// });
+///
+/// let mut line = String::new();
+/// rx.read_line(&mut line)?;
+/// assert_eq!(line, "this is test line 0\n");
+///
+/// let mut rest = String::new();
+/// rx.read_to_string(&mut rest)?;
+/// assert!(rest.ends_with("this is test line 999\n"));
+/// writer.join().unwrap();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Ring`]: struct.Ring.html
+/// [`Ring::new()`]: struct.Ring.html#method.new
+/// [`PipeReader`]: struct.PipeReader.html
+/// [`PipeWriter`]: struct.PipeWriter.html
+pub fn pipe(capacity: usize) -> Result<(PipeReader, PipeWriter)> {
+    let (tx, rx) = Ring::new(capacity)?.split();
+    let signal = Arc::new(Signal {
+        lock: Mutex::new(()),
+        cond: Condvar::new(),
+        closed: AtomicBool::new(false),
+    });
+    Ok((
+        PipeReader {
+            rx,
+            signal: Arc::clone(&signal),
+            timeout: None,
+        },
+        PipeWriter {
+            tx,
+            signal,
+            timeout: None,
+        },
+    ))
+}
+
+/// The read half of a pipe created by [`pipe()`].
+///
+/// Reads block until bytes are available or the [`PipeWriter`] is dropped.
+/// Once the [`PipeWriter`] has been dropped and all bytes have been read, the
+/// reader returns an end-of-file. The non-blocking [`SeqRead`] methods may be
+/// used to inspect the pending bytes without waiting.
+///
+/// [`pipe()`]: fn.pipe.html
+/// [`PipeWriter`]: struct.PipeWriter.html
+/// [`SeqRead`]: trait.SeqRead.html
+#[derive(Debug)]
+pub struct PipeReader {
+    rx: Consumer,
+    signal: Arc<Signal>,
+    timeout: Option<Duration>,
+}
+
+/// The write half of a pipe created by [`pipe()`].
+///
+/// Writes block until space is available. Writing after the [`PipeReader`]
+/// has been dropped results in an error with a kind of `BrokenPipe`. Bytes
+/// written through the non-blocking [`SeqWrite`] methods become readable
+/// when fed into the pipe.
+///
+/// [`pipe()`]: fn.pipe.html
+/// [`PipeReader`]: struct.PipeReader.html
+/// [`SeqWrite`]: trait.SeqWrite.html
+#[derive(Debug)]
+pub struct PipeWriter {
+    tx: Producer,
+    signal: Arc<Signal>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Signal {
+    lock: Mutex<()>,
+    cond: Condvar,
+    closed: AtomicBool,
+}
+
+impl Signal {
+    fn notify(&self) {
+        drop(self.lock.lock().unwrap_or_else(|e| e.into_inner()));
+        self.cond.notify_all();
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Waits until `ready` is true or the other half has closed.
+    ///
+    /// Returns `false` if the timeout elapsed first.
+    fn wait<F: Fn() -> bool>(&self, timeout: Option<Duration>, ready: F) -> bool {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let mut guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        while !ready() && !self.is_closed() {
+            guard = match deadline {
+                None => self.cond.wait(guard).unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.cond
+                        .wait_timeout(guard, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+        true
+    }
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "pipe operation timed out")
+}
+
+impl PipeReader {
+    /// Gets the timeout for blocking reads.
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the timeout for blocking reads.
+    ///
+    /// When a read waits longer than the timeout for bytes to become
+    /// available, it results in an error with a kind of `TimedOut`. A value
+    /// of `None` waits indefinitely.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Tests if the paired [`PipeWriter`] has been dropped.
+    ///
+    /// [`PipeWriter`]: struct.PipeWriter.html
+    pub fn is_closed(&self) -> bool {
+        self.signal.is_closed()
+    }
+}
+
+impl Drop for PipeReader {
+    fn drop(&mut self) {
+        self.signal.close();
+    }
+}
+
+impl SeqRead for PipeReader {
+    fn as_read_ptr(&self) -> *const u8 {
+        self.rx.as_read_ptr()
+    }
+
+    fn read_offset(&self) -> usize {
+        self.rx.read_offset()
+    }
+
+    fn read_len(&self) -> usize {
+        self.rx.read_len()
+    }
+}
+
+impl BufRead for PipeReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let rx = &self.rx;
+        if !self.signal.wait(self.timeout, || rx.read_len() > 0) {
+            return Err(timed_out());
+        }
+        self.rx.fill_buf()
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.rx.consume(len);
+        self.signal.notify();
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.fill_buf()?;
+        self.read_from(buf)
+    }
+}
+
+impl PipeWriter {
+    /// Gets the timeout for blocking writes.
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the timeout for blocking writes.
+    ///
+    /// When a write waits longer than the timeout for space to become
+    /// available, it results in an error with a kind of `TimedOut`. A value
+    /// of `None` waits indefinitely.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Tests if the paired [`PipeReader`] has been dropped.
+    ///
+    /// [`PipeReader`]: struct.PipeReader.html
+    pub fn is_closed(&self) -> bool {
+        self.signal.is_closed()
+    }
+}
+
+impl Drop for PipeWriter {
+    fn drop(&mut self) {
+        self.signal.close();
+    }
+}
+
+impl SeqWrite for PipeWriter {
+    fn as_write_ptr(&mut self) -> *mut u8 {
+        self.tx.as_write_ptr()
+    }
+
+    fn write_offset(&self) -> usize {
+        self.tx.write_offset()
+    }
+
+    fn write_len(&self) -> usize {
+        self.tx.write_len()
+    }
+
+    fn write_capacity(&self) -> usize {
+        self.tx.write_capacity()
+    }
+
+    fn feed(&mut self, len: usize) {
+        self.tx.feed(len);
+        self.signal.notify();
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let tx = &self.tx;
+        if !self.signal.wait(self.timeout, || tx.write_len() > 0) {
+            return Err(timed_out());
+        }
+        if self.signal.is_closed() {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        self.write_into(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
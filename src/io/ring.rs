@@ -1,9 +1,10 @@
-use super::{SeqRead, SeqWrite};
-use crate::os::{map_ring, unmap_ring};
-use crate::{Result, Size};
+use super::{split, Consumer, Producer, SeqRead, SeqWrite};
+use crate::os::{advise, map_ring, unmap_ring};
+use crate::{Advise, Error, Input, Operation, Result, Size};
 
 use std::cmp;
 use std::io::{self, BufRead, Read, Write};
+use std::mem::ManuallyDrop;
 use std::ops::Deref;
 
 /// Fixed-size reliable read/write buffer with sequential address mapping.
@@ -62,6 +63,12 @@ pub struct Ring {
     wpos: u64,
 }
 
+// SAFETY: The circular mapping is exclusively owned by the `Ring`, and both
+// the cursors and the mapped memory are only modified through `&mut self`.
+// Shared references can only observe the readable slice.
+unsafe impl Send for Ring {}
+unsafe impl Sync for Ring {}
+
 impl Ring {
     /// Constructs a new buffer instance.
     ///
@@ -80,6 +87,51 @@ impl Ring {
         })
     }
 
+    /// Constructs a new buffer instance rounded to a multiple of `size`.
+    ///
+    /// This behaves like [`Ring::new()`], but the capacity is rounded up to
+    /// the unit `size` rather than the allocation granularity. When `size` is
+    /// a huge page size, such as from [`Size::huge()`], the ring memory is
+    /// also advised to be backed by transparent huge pages. This is only a
+    /// hint, and the ring falls back to normal pages when unavailable.
+    ///
+    /// The `size` must be a multiple of the allocation granularity, otherwise
+    /// this fails with [`Input::InvalidRange`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::{Ring, SeqWrite};
+    /// use vmap::Size;
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let size = Size::huge().unwrap_or_default();
+    /// let buf = Ring::with_size(4000, size)?;
+    /// assert_eq!(buf.write_capacity(), size.size(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Ring::new()`]: #method.new
+    /// [`Size::huge()`]: ../struct.Size.html#method.huge
+    /// [`Input::InvalidRange`]: ../enum.Input.html#variant.InvalidRange
+    pub fn with_size(hint: usize, size: Size) -> Result<Self> {
+        if Size::alloc().offset(size.size(1)) != 0 {
+            return Err(Error::input(Operation::RingAllocate, Input::InvalidRange));
+        }
+        let len = size.round(hint);
+        let ptr = map_ring(len)?;
+        if size.size(1) > Size::alloc().size(1) {
+            unsafe { advise(ptr, 2 * len, Advise::HugePage) }.unwrap_or_default();
+        }
+        Ok(Self {
+            ptr,
+            len,
+            rpos: 0,
+            wpos: 0,
+        })
+    }
+
     /// Clears the buffer, resetting the filled region to empty.
     ///
     /// The number of initialized bytes is not changed, and the contents of the buffer are not modified.
@@ -87,6 +139,42 @@ impl Ring {
         self.rpos = 0;
         self.wpos = 0;
     }
+
+    /// Splits the buffer into a write half and a read half.
+    ///
+    /// The [`Producer`] and [`Consumer`] may be used from separate threads
+    /// without any locking. Any unconsumed bytes in the buffer remain readable
+    /// by the [`Consumer`]. The mapping is released once both halves have
+    /// been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::{Ring, SeqRead};
+    /// use std::io::{BufRead, Write};
+    /// use std::thread;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let (mut tx, mut rx) = Ring::new(4000).unwrap().split();
+    ///
+    /// let writer = thread::spawn(move || {
+    ///     tx.write_all(b"hello world").unwrap();
+    /// });
+    /// writer.join().unwrap();
+    ///
+    /// assert_eq!(rx.as_read_slice(5), b"hello");
+    /// rx.consume(6);
+    /// assert_eq!(rx.fill_buf()?, b"world");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Producer`]: struct.Producer.html
+    /// [`Consumer`]: struct.Consumer.html
+    pub fn split(self) -> (Producer, Consumer) {
+        let ring = ManuallyDrop::new(self);
+        split::split(ring.ptr, ring.len, ring.rpos, ring.wpos)
+    }
 }
 
 impl Drop for Ring {
@@ -133,7 +221,7 @@ impl SeqWrite for Ring {
 
 impl BufRead for Ring {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
-        Ok(self.as_read_slice(std::usize::MAX))
+        Ok(self.as_read_slice(usize::MAX))
     }
 
     fn consume(&mut self, len: usize) {
@@ -175,6 +263,46 @@ where
     }
 }
 
+/// The readable region is always contiguous, so `chunk()` returns all of the
+/// remaining bytes.
+#[cfg(feature = "bytes")]
+impl bytes::Buf for Ring {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.read_len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.as_read_slice(usize::MAX)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.read_len(), "cannot advance past remaining");
+        self.consume(cnt);
+    }
+}
+
+/// The writable region is always contiguous, so `chunk_mut()` returns all of
+/// the remaining space.
+#[cfg(feature = "bytes")]
+unsafe impl bytes::BufMut for Ring {
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        self.write_len()
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(cnt <= self.write_len(), "cannot advance past remaining");
+        self.feed(cnt);
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut bytes::buf::UninitSlice {
+        bytes::buf::UninitSlice::new(self.as_write_slice(usize::MAX))
+    }
+}
+
 /// Fixed-size lossy read/write buffer with sequential address mapping.
 ///
 /// This uses a circular address mapping scheme. That is, for any buffer of
@@ -223,6 +351,10 @@ pub struct InfiniteRing {
     wpos: u64,
 }
 
+// SAFETY: See `Ring`; the same ownership and mutation rules apply.
+unsafe impl Send for InfiniteRing {}
+unsafe impl Sync for InfiniteRing {}
+
 impl InfiniteRing {
     /// Constructs a new ring buffer instance.
     ///
@@ -281,7 +413,7 @@ impl SeqWrite for InfiniteRing {
 
 impl BufRead for InfiniteRing {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
-        Ok(self.as_read_slice(std::usize::MAX))
+        Ok(self.as_read_slice(usize::MAX))
     }
 
     fn consume(&mut self, len: usize) {
@@ -334,3 +466,23 @@ where
         self.deref()
     }
 }
+
+/// The readable region is always contiguous, so `chunk()` returns all of the
+/// remaining bytes.
+#[cfg(feature = "bytes")]
+impl bytes::Buf for InfiniteRing {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.read_len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.as_read_slice(usize::MAX)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.read_len(), "cannot advance past remaining");
+        self.consume(cnt);
+    }
+}
@@ -0,0 +1,659 @@
+use super::{SeqRead, SeqWrite};
+use crate::os::{flush, map_file, map_ring_fd, recv_fd, send_fd, tmp_open, unmap};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::Seals;
+use crate::{Error, Flush, Input, Operation, Protect, Result, Size};
+
+use std::cmp;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Read, Write};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Fixed-size read/write buffer with sequential address mapping that may be
+/// shared between processes.
+///
+/// This is similar to a [`Ring`], however the buffer is backed by a memory
+/// file descriptor, and the read and write cursors are stored in a header
+/// page within the shared memory. The descriptor may be passed to another
+/// process, such as with [`.send()`] and [`.recv()`], which then maps the
+/// same physical pages. Typically one process writes into the buffer while
+/// the other reads from it, giving a zero-copy pipe where the entire readable
+/// range is always addressable as a single slice.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::{SeqRead, SharedRing};
+/// use std::io::{BufRead, Write};
+/// use std::os::unix::net::UnixStream;
+///
+/// # fn main() -> vmap::Result<()> {
+/// let (a, b) = UnixStream::pair()?;
+///
+/// let mut tx = SharedRing::new(4000)?;
+/// tx.send(&a)?;
+///
+/// // ... typically in another process
+/// let mut rx = SharedRing::recv(&b)?;
+///
+/// tx.write_all(b"hello world")?;
+/// assert_eq!(rx.as_read_slice(5), b"hello");
+/// rx.consume(6);
+/// assert_eq!(rx.fill_buf()?, b"world");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Ring`]: struct.Ring.html
+/// [`.send()`]: #method.send
+/// [`.recv()`]: #method.recv
+#[derive(Debug)]
+pub struct SharedRing {
+    file: File,
+    map: RingMap,
+}
+
+impl SharedRing {
+    /// Constructs a new shared buffer instance.
+    ///
+    /// The hint is a minimum size for the buffer. This size will be rounded up
+    /// to the nearest page size for the actual capacity. An additional page is
+    /// used for the shared header.
+    pub fn new(hint: usize) -> Result<Self> {
+        let len = Size::alloc().round(hint);
+        let fd = tmp_open(RingMap::data_offset() + len)?;
+        let file = unsafe { File::from_raw_fd(fd) };
+        let map = RingMap::init(&file, len)?;
+        Ok(Self { file, map })
+    }
+
+    /// Attaches to a shared buffer from a descriptor.
+    ///
+    /// The descriptor must have been exported from a `SharedRing`, such as
+    /// with [`.try_clone_fd()`]. The mapping is validated against the shared
+    /// header before use.
+    ///
+    /// No seals are required of the descriptor, so a peer may still shrink
+    /// the memory while it is mapped, causing an access to fault with
+    /// `SIGBUS`. Use [`SharedRing::from_fd_sealed()`] when the peer is not
+    /// trusted.
+    ///
+    /// [`.try_clone_fd()`]: #method.try_clone_fd
+    /// [`SharedRing::from_fd_sealed()`]: #method.from_fd_sealed
+    pub fn from_fd(fd: OwnedFd) -> Result<Self> {
+        let file = File::from(fd);
+        let map = RingMap::attach(&file, false)?;
+        Ok(Self { file, map })
+    }
+
+    /// Attaches to a shared buffer from a descriptor that has all of the
+    /// required `seals`.
+    ///
+    /// This is the same as [`SharedRing::from_fd()`], except that attaching
+    /// fails with an [`Error`] whose operation is [`Operation::MemoryFd`]
+    /// when the descriptor is missing one of the seals, or does not support
+    /// sealing. Requiring [`Seals::SHRINK`] ensures the mapped memory cannot
+    /// be truncated by the peer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::io::SharedRing;
+    /// use vmap::Seals;
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let tx = SharedRing::new(4000)?;
+    /// let seals = Seals::SHRINK | Seals::GROW;
+    /// assert!(SharedRing::from_fd_sealed(tx.try_clone_fd()?, seals).is_err());
+    ///
+    /// tx.add_seals(seals)?;
+    /// assert!(SharedRing::from_fd_sealed(tx.try_clone_fd()?, seals).is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SharedRing::from_fd()`]: #method.from_fd
+    /// [`Error`]: ../struct.Error.html
+    /// [`Operation::MemoryFd`]: ../enum.Operation.html#variant.MemoryFd
+    /// [`Seals::SHRINK`]: ../struct.Seals.html#associatedconstant.SHRINK
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn from_fd_sealed(fd: OwnedFd, seals: Seals) -> Result<Self> {
+        if !seals.is_empty() && !Seals::of(&fd)?.contains(seals) {
+            return Err(Error::input(Operation::MemoryFd, Input::MissingSeals));
+        }
+        Self::from_fd(fd)
+    }
+
+    /// Adds seals to restrict further changes to the shared memory.
+    ///
+    /// This allows a peer to attach using [`SharedRing::from_fd_sealed()`].
+    /// Seals that prevent writes cannot be added while the buffer is mapped.
+    ///
+    /// [`SharedRing::from_fd_sealed()`]: #method.from_fd_sealed
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn add_seals(&self, seals: Seals) -> Result<()> {
+        seals.add_to(self.file.as_fd())
+    }
+
+    /// Creates a new owned descriptor for the shared memory.
+    ///
+    /// The descriptor may be passed to another process and attached using
+    /// [`SharedRing::from_fd()`].
+    ///
+    /// [`SharedRing::from_fd()`]: #method.from_fd
+    pub fn try_clone_fd(&self) -> io::Result<OwnedFd> {
+        self.file.as_fd().try_clone_to_owned()
+    }
+
+    /// Sends the shared memory descriptor over a Unix domain socket.
+    ///
+    /// The receiving side attaches to the buffer with [`SharedRing::recv()`].
+    ///
+    /// [`SharedRing::recv()`]: #method.recv
+    pub fn send(&self, sock: &UnixStream) -> Result<()> {
+        send_fd(sock, self.file.as_fd())
+    }
+
+    /// Receives a shared memory descriptor over a Unix domain socket and
+    /// attaches to the buffer.
+    ///
+    /// As with [`SharedRing::from_fd()`], no seals are required. Use
+    /// [`SharedRing::recv_sealed()`] when the peer is not trusted.
+    ///
+    /// [`SharedRing::from_fd()`]: #method.from_fd
+    /// [`SharedRing::recv_sealed()`]: #method.recv_sealed
+    pub fn recv(sock: &UnixStream) -> Result<Self> {
+        Self::from_fd(recv_fd(sock)?)
+    }
+
+    /// Receives a shared memory descriptor over a Unix domain socket and
+    /// attaches to the buffer if it has all of the required `seals`.
+    ///
+    /// See [`SharedRing::from_fd_sealed()`] for details.
+    ///
+    /// [`SharedRing::from_fd_sealed()`]: #method.from_fd_sealed
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_sealed(sock: &UnixStream, seals: Seals) -> Result<Self> {
+        Self::from_fd_sealed(recv_fd(sock)?, seals)
+    }
+}
+
+impl AsFd for SharedRing {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl AsRawFd for SharedRing {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl SeqRead for SharedRing {
+    fn as_read_ptr(&self) -> *const u8 {
+        self.map.ptr
+    }
+
+    fn read_offset(&self) -> usize {
+        self.map.read_offset()
+    }
+
+    fn read_len(&self) -> usize {
+        self.map.read_len()
+    }
+}
+
+impl SeqWrite for SharedRing {
+    fn as_write_ptr(&mut self) -> *mut u8 {
+        self.map.ptr
+    }
+
+    fn write_offset(&self) -> usize {
+        self.map.write_offset()
+    }
+
+    fn write_len(&self) -> usize {
+        self.map.write_len()
+    }
+
+    fn write_capacity(&self) -> usize {
+        self.map.len
+    }
+
+    fn feed(&mut self, len: usize) {
+        self.map.feed(len)
+    }
+}
+
+impl BufRead for SharedRing {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_read_slice(usize::MAX))
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.map.consume(len)
+    }
+}
+
+impl Read for SharedRing {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_from(buf)
+    }
+}
+
+impl Write for SharedRing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_into(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Fixed-size read/write buffer with sequential address mapping that persists
+/// in a file.
+///
+/// This is similar to a [`Ring`], however both halves of the circular mapping
+/// refer to a regular file rather than anonymous memory. The read and write
+/// cursors are stored in a header page at the start of the file. Reopening
+/// the file, such as after a restart, restores any unconsumed bytes. This
+/// allows the ring to be used as a durable spool queue.
+///
+/// Changes are written back by the system as with any shared file mapping.
+/// To ensure durability after a crash of the system, use [`.flush()`] with
+/// [`Flush::Sync`]. The `flush` method of `Write` only starts the write back
+/// using [`Flush::Async`], so it does not block. Until a sync returns, the system may write back the data and
+/// header pages in any order, so after a crash the persisted cursors may
+/// refer to bytes that were not written. If the system crashes while a new
+/// buffer is created, leaving an all-zero header, the buffer is initialized
+/// again as empty when next opened.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::{FileRing, SeqRead};
+/// use vmap::Flush;
+/// use std::io::{BufRead, Write};
+/// use std::path::PathBuf;
+///
+/// # fn main() -> vmap::Result<()> {
+/// # let tmp = tempdir::TempDir::new("vmap")?;
+/// let path: PathBuf = /* path to file */
+/// # tmp.path().join("example");
+/// {
+///     let mut ring = FileRing::open(&path, 4000)?;
+///     ring.write_all(b"hello world")?;
+///     ring.consume(6);
+///     ring.flush(Flush::Sync)?;
+/// }
+///
+/// let mut ring = FileRing::open(&path, 4000)?;
+/// assert_eq!(ring.fill_buf()?, b"world");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Ring`]: struct.Ring.html
+/// [`.flush()`]: #method.flush
+/// [`Flush::Sync`]: ../enum.Flush.html#variant.Sync
+/// [`Flush::Async`]: ../enum.Flush.html#variant.Async
+#[derive(Debug)]
+pub struct FileRing {
+    file: File,
+    map: RingMap,
+}
+
+impl FileRing {
+    /// Opens or creates a persistent buffer at the path.
+    ///
+    /// If the file is empty or does not exist, a new buffer is created. The
+    /// hint is a minimum size for the buffer, and it will be rounded up to the
+    /// nearest page size for the actual capacity. An additional page is used
+    /// for the header.
+    ///
+    /// Otherwise, the existing buffer is validated and mapped with its
+    /// original capacity, and the hint is ignored. An existing buffer with an
+    /// all-zero header was never fully created, so it is initialized as
+    /// empty with the capacity of the file.
+    pub fn open<P: AsRef<Path>>(path: P, hint: usize) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| Error::io(Operation::MapFile, e))?;
+        Self::from_file(file, hint)
+    }
+
+    /// Maps a persistent buffer from an open file.
+    ///
+    /// The file must be opened with read and write access. See
+    /// [`FileRing::open()`] for how the `hint` is applied.
+    ///
+    /// [`FileRing::open()`]: #method.open
+    pub fn from_file(file: File, hint: usize) -> Result<Self> {
+        let flen = file
+            .metadata()
+            .map_err(|e| Error::io(Operation::MapFile, e))?
+            .len();
+        let map = if flen == 0 {
+            RingMap::init(&file, Size::alloc().round(hint))?
+        } else {
+            RingMap::attach(&file, true)?
+        };
+        Ok(Self { file, map })
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Writes both the data region and the header back to the filesystem.
+    pub fn flush(&self, mode: Flush) -> Result<()> {
+        self.flush_data(mode)?;
+        self.flush_header(mode)
+    }
+
+    /// Writes modifications of the data region back to the filesystem.
+    pub fn flush_data(&self, mode: Flush) -> Result<()> {
+        unsafe { flush(self.map.ptr, &self.file, self.map.len, mode) }
+    }
+
+    /// Writes modifications of the header back to the filesystem.
+    pub fn flush_header(&self, mode: Flush) -> Result<()> {
+        unsafe { flush(self.map.hdr, &self.file, RingMap::header_len(), mode) }
+    }
+}
+
+impl AsFd for FileRing {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl AsRawFd for FileRing {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl SeqRead for FileRing {
+    fn as_read_ptr(&self) -> *const u8 {
+        self.map.ptr
+    }
+
+    fn read_offset(&self) -> usize {
+        self.map.read_offset()
+    }
+
+    fn read_len(&self) -> usize {
+        self.map.read_len()
+    }
+}
+
+impl SeqWrite for FileRing {
+    fn as_write_ptr(&mut self) -> *mut u8 {
+        self.map.ptr
+    }
+
+    fn write_offset(&self) -> usize {
+        self.map.write_offset()
+    }
+
+    fn write_len(&self) -> usize {
+        self.map.write_len()
+    }
+
+    fn write_capacity(&self) -> usize {
+        self.map.len
+    }
+
+    fn feed(&mut self, len: usize) {
+        self.map.feed(len)
+    }
+}
+
+impl BufRead for FileRing {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_read_slice(usize::MAX))
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.map.consume(len)
+    }
+}
+
+impl Read for FileRing {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_from(buf)
+    }
+}
+
+impl Write for FileRing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_into(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        FileRing::flush(self, Flush::Async).map_err(io::Error::from)
+    }
+}
+
+/// The header stored in the first page of a descriptor backed ring.
+#[repr(C)]
+struct Header {
+    magic: u64,
+    off: u64,
+    len: u64,
+    rpos: AtomicU64,
+    wpos: AtomicU64,
+}
+
+const MAGIC: u64 = u64::from_be_bytes(*b"vmapring");
+
+impl Header {
+    /// Tests if the header was never written.
+    fn is_zeroed(&self) -> bool {
+        self.magic == 0
+            && self.off == 0
+            && self.len == 0
+            && self.rpos.load(Ordering::Relaxed) == 0
+            && self.wpos.load(Ordering::Relaxed) == 0
+    }
+}
+
+/// The header and circular mappings for a descriptor backed ring.
+///
+/// This is used for both the [`SharedRing`] and the [`FileRing`]. The header
+/// is at the start of the file, and the ring data follows at the offset that
+/// is recorded in the header. A new ring places the data after the first
+/// allocation unit.
+#[derive(Debug)]
+struct RingMap {
+    hdr: *mut u8,
+    ptr: *mut u8,
+    off: usize,
+    len: usize,
+}
+
+// SAFETY: The mappings are exclusively owned by the `RingMap`, and the
+// cursors in the header are only accessed atomically.
+unsafe impl Send for RingMap {}
+unsafe impl Sync for RingMap {}
+
+impl RingMap {
+    /// Gets the byte offset of the ring data for a new ring.
+    fn data_offset() -> usize {
+        Size::alloc().size(1)
+    }
+
+    /// Gets the length of the header mapping.
+    fn header_len() -> usize {
+        Size::page().size(1)
+    }
+
+    /// Maps a file as a new ring, resetting the header.
+    fn init(file: &File, len: usize) -> Result<Self> {
+        let off = Self::data_offset();
+        file.set_len((off + len) as u64)
+            .map_err(|e| Error::io(Operation::MapFile, e))?;
+        let hdr = map_file(file, 0, Self::header_len(), Protect::ReadWrite)?;
+        let map = Self::map(file, hdr, off, len)?;
+        map.reset();
+        Ok(map)
+    }
+
+    /// Maps a file previously initialized as a ring.
+    ///
+    /// The data offset is taken from the header rather than the current page
+    /// size, as the ring may have been created on a system with larger pages.
+    /// When `recover` is set, an all-zero header is treated as a ring that
+    /// was never fully initialized, and it is reset rather than rejected.
+    fn attach(file: &File, recover: bool) -> Result<Self> {
+        let invalid = || Error::input(Operation::MapFile, Input::InvalidHeader);
+        let flen = file
+            .metadata()
+            .map_err(|e| Error::io(Operation::MapFile, e))?
+            .len() as usize;
+        if flen < Self::header_len() {
+            return Err(invalid());
+        }
+
+        let hdr = map_file(file, 0, Self::header_len(), Protect::ReadWrite)?;
+        let header = unsafe { &*(hdr as *const Header) };
+        let reset = recover && header.is_zeroed();
+        let range = if reset {
+            let off = Self::data_offset();
+            Some((off, flen.saturating_sub(off)))
+        } else if header.magic == MAGIC {
+            usize::try_from(header.off)
+                .ok()
+                .zip(usize::try_from(header.len).ok())
+        } else {
+            None
+        };
+        let align = Size::alloc();
+        let (off, len) = match range {
+            Some((off, len))
+                if off >= Self::header_len()
+                    && len > 0
+                    && align.offset(off) == 0
+                    && align.offset(len) == 0
+                    && off.checked_add(len) == Some(flen) =>
+            {
+                (off, len)
+            }
+            _ => {
+                unsafe { unmap(hdr, Self::header_len()) }.unwrap_or_default();
+                return Err(invalid());
+            }
+        };
+
+        let map = Self::map(file, hdr, off, len)?;
+        if reset {
+            map.reset();
+            return Ok(map);
+        }
+        let hdr = map.header();
+        let (rpos, wpos) = (
+            hdr.rpos.load(Ordering::Acquire),
+            hdr.wpos.load(Ordering::Acquire),
+        );
+        if rpos > wpos || wpos - rpos > hdr.len {
+            return Err(invalid());
+        }
+        Ok(map)
+    }
+
+    /// Maps the circular data region of a file following a mapped header.
+    ///
+    /// The header is unmapped if the data region cannot be mapped.
+    fn map(file: &File, hdr: *mut u8, off: usize, len: usize) -> Result<Self> {
+        match map_ring_fd(file.as_raw_fd(), off, len) {
+            Ok(ptr) => Ok(Self { hdr, ptr, off, len }),
+            Err(err) => {
+                unsafe { unmap(hdr, Self::header_len()) }.unwrap_or_default();
+                Err(err)
+            }
+        }
+    }
+
+    /// Writes a new header for an empty ring.
+    fn reset(&self) {
+        unsafe {
+            let hdr = self.hdr as *mut Header;
+            (*hdr).magic = MAGIC;
+            (*hdr).off = self.off as u64;
+            (*hdr).len = self.len as u64;
+            (*hdr).rpos = AtomicU64::new(0);
+            (*hdr).wpos = AtomicU64::new(0);
+        }
+    }
+
+    /// Gets a reference to the shared header.
+    fn header(&self) -> &Header {
+        unsafe { &*(self.hdr as *const Header) }
+    }
+
+    fn read_offset(&self) -> usize {
+        self.header().rpos.load(Ordering::Relaxed) as usize % self.len
+    }
+
+    fn read_len(&self) -> usize {
+        let hdr = self.header();
+        let wpos = hdr.wpos.load(Ordering::Acquire);
+        let rpos = hdr.rpos.load(Ordering::Relaxed);
+        self.distance(rpos, wpos).unwrap_or(0)
+    }
+
+    fn write_offset(&self) -> usize {
+        self.header().wpos.load(Ordering::Relaxed) as usize % self.len
+    }
+
+    fn write_len(&self) -> usize {
+        let hdr = self.header();
+        let rpos = hdr.rpos.load(Ordering::Acquire);
+        let wpos = hdr.wpos.load(Ordering::Relaxed);
+        self.distance(rpos, wpos).map_or(0, |n| self.len - n)
+    }
+
+    /// Gets the number of bytes between the read and write cursors.
+    ///
+    /// The header may be modified by another process at any time, so the
+    /// cursors are checked on every load. If they are further apart than the
+    /// capacity, the ring is corrupt and `None` is returned.
+    fn distance(&self, rpos: u64, wpos: u64) -> Option<usize> {
+        let n = wpos.wrapping_sub(rpos);
+        if n > self.len as u64 {
+            None
+        } else {
+            Some(n as usize)
+        }
+    }
+
+    fn feed(&mut self, len: usize) {
+        let len = cmp::min(len, self.write_len()) as u64;
+        self.header().wpos.fetch_add(len, Ordering::Release);
+    }
+
+    fn consume(&mut self, len: usize) {
+        let len = cmp::min(len, self.read_len()) as u64;
+        self.header().rpos.fetch_add(len, Ordering::Release);
+    }
+}
+
+impl Drop for RingMap {
+    fn drop(&mut self) {
+        unsafe {
+            unmap(self.ptr, 2 * self.len).unwrap_or_default();
+            unmap(self.hdr, Self::header_len()).unwrap_or_default();
+        }
+    }
+}
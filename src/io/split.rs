@@ -0,0 +1,159 @@
+use super::{SeqRead, SeqWrite};
+use crate::os::unmap_ring;
+
+use std::cmp;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The write half of a [`Ring`] created by [`Ring::split()`].
+///
+/// The `Producer` may be moved into a different thread from its paired
+/// [`Consumer`]. Bytes written become visible to the [`Consumer`] once fed
+/// into the buffer. The writable length only increases as the [`Consumer`]
+/// releases space by consuming it.
+///
+/// [`Ring`]: struct.Ring.html
+/// [`Ring::split()`]: struct.Ring.html#method.split
+/// [`Consumer`]: struct.Consumer.html
+#[derive(Debug)]
+pub struct Producer {
+    ring: Arc<Shared>,
+}
+
+/// The read half of a [`Ring`] created by [`Ring::split()`].
+///
+/// The `Consumer` may be moved into a different thread from its paired
+/// [`Producer`]. Just as with the [`Ring`], the entire readable range is
+/// always addressable as a single contiguous slice, even when the written
+/// bytes wrap around the end of the buffer.
+///
+/// [`Ring`]: struct.Ring.html
+/// [`Ring::split()`]: struct.Ring.html#method.split
+/// [`Producer`]: struct.Producer.html
+#[derive(Debug)]
+pub struct Consumer {
+    ring: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    ptr: *mut u8,
+    len: usize,
+    rpos: AtomicU64,
+    wpos: AtomicU64,
+}
+
+// SAFETY: The `Producer` only writes to the region between the write and read
+// cursors, and the `Consumer` only reads from the region between the read and
+// write cursors. Each cursor is only modified by its owning half, and it is
+// released after the memory it covers is accessed.
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        unsafe { unmap_ring(self.ptr, self.len) }.unwrap_or_default();
+    }
+}
+
+pub(super) fn split(ptr: *mut u8, len: usize, rpos: u64, wpos: u64) -> (Producer, Consumer) {
+    let ring = Arc::new(Shared {
+        ptr,
+        len,
+        rpos: AtomicU64::new(rpos),
+        wpos: AtomicU64::new(wpos),
+    });
+    (
+        Producer {
+            ring: Arc::clone(&ring),
+        },
+        Consumer { ring },
+    )
+}
+
+impl Producer {
+    /// Tests if the paired [`Consumer`] has been dropped.
+    ///
+    /// [`Consumer`]: struct.Consumer.html
+    pub fn is_closed(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+}
+
+impl Consumer {
+    /// Tests if the paired [`Producer`] has been dropped.
+    ///
+    /// [`Producer`]: struct.Producer.html
+    pub fn is_closed(&self) -> bool {
+        Arc::strong_count(&self.ring) == 1
+    }
+}
+
+impl SeqWrite for Producer {
+    fn as_write_ptr(&mut self) -> *mut u8 {
+        self.ring.ptr
+    }
+
+    fn write_offset(&self) -> usize {
+        self.ring.wpos.load(Ordering::Relaxed) as usize % self.ring.len
+    }
+
+    fn write_len(&self) -> usize {
+        let rpos = self.ring.rpos.load(Ordering::Acquire);
+        let wpos = self.ring.wpos.load(Ordering::Relaxed);
+        self.ring.len - (wpos - rpos) as usize
+    }
+
+    fn write_capacity(&self) -> usize {
+        self.ring.len
+    }
+
+    fn feed(&mut self, len: usize) {
+        let len = cmp::min(len, self.write_len()) as u64;
+        self.ring.wpos.fetch_add(len, Ordering::Release);
+    }
+}
+
+impl Write for Producer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_into(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SeqRead for Consumer {
+    fn as_read_ptr(&self) -> *const u8 {
+        self.ring.ptr
+    }
+
+    fn read_offset(&self) -> usize {
+        self.ring.rpos.load(Ordering::Relaxed) as usize % self.ring.len
+    }
+
+    fn read_len(&self) -> usize {
+        let wpos = self.ring.wpos.load(Ordering::Acquire);
+        let rpos = self.ring.rpos.load(Ordering::Relaxed);
+        (wpos - rpos) as usize
+    }
+}
+
+impl BufRead for Consumer {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_read_slice(usize::MAX))
+    }
+
+    fn consume(&mut self, len: usize) {
+        let len = cmp::min(len, self.read_len()) as u64;
+        self.ring.rpos.fetch_add(len, Ordering::Release);
+    }
+}
+
+impl Read for Consumer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_from(buf)
+    }
+}
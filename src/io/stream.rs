@@ -0,0 +1,153 @@
+use super::{BufReader, BufWriter, IntoInnerError, Ring, WriterPanicked};
+use crate::Result;
+
+use std::io::{self, BufRead, Read, Write};
+
+/// The `BufStream` adds buffering to both directions of a stream.
+///
+/// This combines a [`BufReader`] and a [`BufWriter`] over a single stream,
+/// such as a socket, using a separate [`Ring`] for each direction. Reads are
+/// served from the read buffer, and writes are collected in the write buffer
+/// until it fills or is flushed. Just as with the [`BufWriter`], any buffered
+/// output is written when the `BufStream` is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::io::BufStream;
+/// # use std::io::prelude::*;
+/// # use std::net::{TcpListener, TcpStream};
+///
+/// # fn main() -> std::io::Result<()> {
+/// # let srv = TcpListener::bind("127.0.0.1:0")?;
+/// let sock = TcpStream::connect(srv.local_addr().unwrap())?;
+/// # let (mut cli, _addr) = srv.accept()?;
+/// let mut stream = BufStream::new(sock, 4000).expect("failed to create buffer");
+/// stream.write_all(b"ping\n")?;
+/// stream.flush()?;
+/// # let mut buf = [0u8; 5];
+/// # cli.read_exact(&mut buf)?;
+/// # cli.write_all(b"pong\n")?;
+///
+/// let mut line = String::new();
+/// stream.read_line(&mut line)?;
+/// assert_eq!(line, "pong\n");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`BufReader`]: struct.BufReader.html
+/// [`BufWriter`]: struct.BufWriter.html
+/// [`Ring`]: struct.Ring.html
+pub struct BufStream<S: Read + Write> {
+    inner: BufReader<BufWriter<S>>,
+}
+
+impl<S: Read + Write> BufStream<S> {
+    /// Creates a new `BufStream` using the same capacity for both buffers.
+    pub fn new(inner: S, capacity: usize) -> Result<Self> {
+        Self::with_capacities(inner, capacity, capacity)
+    }
+
+    /// Creates a new `BufStream` with separate read and write capacities.
+    pub fn with_capacities(inner: S, read: usize, write: usize) -> Result<Self> {
+        Ok(Self {
+            inner: BufReader::new(BufWriter::new(inner, write)?, read)?,
+        })
+    }
+
+    /// Get the low-water level of the read buffer.
+    ///
+    /// See [`BufReader::set_lowat()`] for details.
+    ///
+    /// [`BufReader::set_lowat()`]: struct.BufReader.html#method.set_lowat
+    #[inline]
+    pub fn lowat(&self) -> usize {
+        self.inner.lowat()
+    }
+
+    /// Set the low-water level of the read buffer.
+    #[inline]
+    pub fn set_lowat(&mut self, val: usize) {
+        self.inner.set_lowat(val)
+    }
+
+    /// Gets a reference to the underlying stream.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref().get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// Reading from or writing to the stream directly may cause data to be
+    /// observed out of order with the buffered data.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        self.inner.get_mut().get_mut()
+    }
+
+    /// Returns a reference to the internally buffered read data.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        self.inner.buffer()
+    }
+
+    /// Unwraps this `BufStream`, returning the underlying stream.
+    ///
+    /// The write buffer is flushed first, and any buffered read data is
+    /// discarded. On `Err`, the [`IntoInnerError`] holds the [`BufWriter`]
+    /// for recovering the unwritten data.
+    ///
+    /// [`BufWriter`]: struct.BufWriter.html
+    /// [`IntoInnerError`]: struct.IntoInnerError.html
+    pub fn into_inner(self) -> std::result::Result<S, IntoInnerError<S>> {
+        self.inner.into_inner().into_inner()
+    }
+
+    /// Disassembles this `BufStream` into the underlying stream, the read
+    /// buffer, and the write buffer.
+    ///
+    /// Neither buffer is flushed or discarded. As with
+    /// [`BufWriter::into_parts()`], the write buffer is wrapped in a
+    /// [`WriterPanicked`] error if the stream panicked during a write.
+    ///
+    /// [`BufWriter::into_parts()`]: struct.BufWriter.html#method.into_parts
+    /// [`WriterPanicked`]: struct.WriterPanicked.html
+    pub fn into_parts(self) -> (S, Ring, std::result::Result<Ring, WriterPanicked>) {
+        let (writer, rd) = self.inner.into_parts();
+        let (inner, wr) = writer.into_parts();
+        (inner, rd, wr)
+    }
+}
+
+impl<S: Read + Write> Read for BufStream<S> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<S: Read + Write> BufRead for BufStream<S> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+impl<S: Read + Write> Write for BufStream<S> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
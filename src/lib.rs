@@ -9,17 +9,21 @@
 //! for mapping. That is, it never clones it or in any way retains it. While
 //! this has some implications for the API (i.e. [`.flush()`]), it cannot cause
 //! bugs outside of this library through `File`'s leaky abstraction when cloned
-//! and then closed.
+//! and then closed. When owning the `File` is preferred, the [`MappedFile`]
+//! type explicitly bundles it with the mapping.
 //!
 //! The [`Map`] and [`MapMut`] types are primary means for allocating virtual
 //! memory regions, both for a file and anonymously. Generally, the
 //! [`Map::with_options()`] and [`MapMut::with_options()`] are used to specify
-//! the mapping requirements. See [`Options`] for more information.
+//! the mapping requirements. See [`Options`] for more information. On unix
+//! targets, an anonymous allocation may also be created as a [`MemFd`] that
+//! can be mapped again or passed to another process.
 //!
 //! The [`MapMut`] type maintains interior mutability for the mapped memory,
 //! while the [`Map`] is read-only. However, it is possible to convert between
 //! these types ([`.into_map_mut()`] and [`.into_map()`]) assuming the proper
-//! [`Options`] are specified.
+//! [`Options`] are specified. A [`Map`] may also be shared as a cheaply
+//! cloneable [`SharedMap`], which can be sliced without copying.
 //!
 //! Additionally, a variety of buffer implementations are provided in the
 //! [`vmap::io`] module. The [`Ring`] and [`InfiniteRing`] use cross-platform
@@ -137,9 +141,12 @@
 //! [`Map::with_options()`]: struct.Map.html#method.with_options
 //! [`MapMut::with_options()`]: struct.MapMut.html#method.with_options
 //! [`MapMut`]: struct.MapMut.html
+//! [`MappedFile`]: struct.MappedFile.html
+//! [`MemFd`]: struct.MemFd.html
 //! [`Map`]: struct.Map.html
 //! [`Options`]: struct.Options.html
 //! [`Ring`]: io/struct.Ring.html
+//! [`SharedMap`]: struct.SharedMap.html
 //! [`vmap::io`]: io/index.html
 
 #![deny(missing_docs)]
@@ -160,6 +167,22 @@ pub use self::error::{ConvertResult, Error, Input, Operation, Result};
 mod map;
 pub use self::map::{Map, MapMut, Options};
 
+mod mapped;
+pub use self::mapped::MappedFile;
+
+#[cfg(unix)]
+mod memfd;
+#[cfg(unix)]
+pub use self::memfd::MemFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::memfd::Seals;
+
+mod reserve;
+pub use self::reserve::Reservation;
+
+mod shared;
+pub use self::shared::SharedMap;
+
 #[cfg(feature = "io")]
 pub mod io;
 
@@ -186,6 +209,21 @@ pub enum Flush {
 }
 
 /// Hint for the access pattern of the underlying mapping.
+///
+/// The first five variants are available on every platform, although some
+/// platforms may treat them as a no-op. The remaining variants correspond to
+/// Linux `madvise` options and are only supported where the platform provides
+/// them. Applying an unsupported variant results in an [`Error`] with a kind
+/// of `Unsupported`.
+///
+/// The `Free` and `Remove` variants may discard or zero the contents of the
+/// memory, as does `WillNotNeed` for a private mapping, such as one created
+/// using `Protect::ReadCopy`. These are destructive, and are only accepted by
+/// [`MapMut::advise_destructive()`], which requires exclusive access to the
+/// mapping.
+///
+/// [`Error`]: struct.Error.html
+/// [`MapMut::advise_destructive()`]: struct.MapMut.html#method.advise_destructive
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Advise {
     /// Use the system default behavior.
@@ -198,6 +236,67 @@ pub enum Advise {
     WillNeed,
     /// The map is not expected to be accessed soon.
     WillNotNeed,
+    /// The pages may be lazily freed, and their contents discarded, unless
+    /// they are written to before being reclaimed.
+    Free,
+    /// The pages and any backing store are removed, leaving a hole that reads
+    /// back as zeroes.
+    Remove,
+    /// The pages are not made available to a child process after a fork.
+    DontFork,
+    /// Undo the effect of a prior `DontFork`.
+    DoFork,
+    /// The pages are excluded from a core dump.
+    DontDump,
+    /// Undo the effect of a prior `DontDump`.
+    DoDump,
+    /// The pages should be backed by transparent huge pages.
+    HugePage,
+    /// The pages should not be backed by transparent huge pages.
+    NoHugePage,
+    /// The pages should be deactivated, making them a better candidate for
+    /// reclaim under memory pressure.
+    Cold,
+    /// The pages should be reclaimed immediately.
+    PageOut,
+    /// The pages are zero-filled in a child process after a fork.
+    WipeOnFork,
+    /// Prefault the page tables for reading.
+    PopulateRead,
+    /// Prefault the page tables for writing.
+    PopulateWrite,
+}
+
+impl Advise {
+    /// Tests if the advise may discard or zero the contents of any mapping.
+    ///
+    /// This does not include `WillNotNeed`, which only discards the contents
+    /// of a private mapping.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Advise::Free | Advise::Remove)
+    }
+}
+
+/// Huge page strategy for an anonymous allocation.
+///
+/// For usage information, see the [`.huge()`] method of the [`Options`]
+/// builder type.
+///
+/// [`.huge()`]: struct.Options.html#method.huge
+/// [`Options`]: struct.Options.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HugePage {
+    /// Advise the system to back the allocation with transparent huge pages.
+    ///
+    /// This is only a hint. When transparent huge pages are unavailable the
+    /// allocation silently uses the normal page size.
+    Transparent,
+    /// Allocate the mapping from the explicit huge page pool using a page
+    /// size from [`Size::huge()`] or [`Size::huge_sizes()`].
+    ///
+    /// [`Size::huge()`]: struct.Size.html#method.huge
+    /// [`Size::huge_sizes()`]: struct.Size.html#method.huge_sizes
+    Explicit(Size),
 }
 
 /// Byte extent type used for length and resize options.
@@ -300,7 +399,7 @@ fn load_system_info() -> (u32, u32) {
 /// let size = size.size(3);
 /// println!("3 pages are {} bytes", size);
 /// ```
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Size(usize);
 
 impl Size {
@@ -322,6 +421,43 @@ impl Size {
         unsafe { Self::with_size(allocation_size()) }
     }
 
+    /// Creates a type for calculating huge page numbers and byte offsets.
+    ///
+    /// The size is determined from the system's default huge page size. This
+    /// returns `None` when explicit huge pages are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::Size;
+    ///
+    /// if let Some(size) = Size::huge() {
+    ///     println!("the default huge page size is {} bytes", size.size(1));
+    ///     assert!(size.size(1) > vmap::page_size());
+    /// }
+    /// ```
+    pub fn huge() -> Option<Self> {
+        self::os::huge_page_size().map(Self)
+    }
+
+    /// Lists all huge page sizes supported by the system.
+    ///
+    /// The sizes are ordered from smallest to largest. The list is empty when
+    /// explicit huge pages are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::Size;
+    ///
+    /// for size in Size::huge_sizes() {
+    ///     println!("{} byte huge pages are supported", size.size(1));
+    /// }
+    /// ```
+    pub fn huge_sizes() -> Vec<Self> {
+        self::os::huge_page_sizes().into_iter().map(Self).collect()
+    }
+
     /// Creates a type for calculating allocations numbers and byte offsets
     /// using a known size.
     ///
@@ -523,7 +659,7 @@ pub trait SpanMut: Span + DerefMut {
     }
 }
 
-impl<'a> Span for &'a [u8] {
+impl Span for &[u8] {
     #[inline]
     fn len(&self) -> usize {
         <[u8]>::len(self)
@@ -535,7 +671,7 @@ impl<'a> Span for &'a [u8] {
     }
 }
 
-impl<'a> Span for &'a mut [u8] {
+impl Span for &mut [u8] {
     #[inline]
     fn len(&self) -> usize {
         <[u8]>::len(self)
@@ -547,7 +683,7 @@ impl<'a> Span for &'a mut [u8] {
     }
 }
 
-impl<'a> SpanMut for &'a mut [u8] {
+impl SpanMut for &mut [u8] {
     #[inline]
     fn as_mut_ptr(&mut self) -> *mut u8 {
         <[u8]>::as_mut_ptr(self)
@@ -559,11 +695,19 @@ mod sealed {
 
     impl Span for super::Map {}
     impl Span for super::MapMut {}
-    impl<'a> Span for &'a [u8] {}
-    impl<'a> Span for &'a mut [u8] {}
+    impl Span for super::MappedFile {}
+    impl Span for super::SharedMap {}
+    impl Span for &[u8] {}
+    impl Span for &mut [u8] {}
 
     pub trait FromPtr {
-        unsafe fn from_ptr(ptr: *mut u8, len: usize) -> Self;
+        unsafe fn from_ptr(
+            ptr: *mut u8,
+            len: usize,
+            off: usize,
+            page: super::Size,
+            prot: super::Protect,
+        ) -> Self;
     }
 
     pub trait Scalar: Default {}
@@ -586,7 +730,7 @@ mod sealed {
 
 #[inline]
 fn assert_alignment<T>(offset: usize, ptr: *const u8) {
-    if unsafe { ptr.add(offset) } as usize % mem::align_of::<T>() != 0 {
+    if unsafe { ptr.add(offset) } as usize & (mem::align_of::<T>() - 1) != 0 {
         panic!(
             "offset improperly aligned: the requirement is {} but the offset is +{}/-{}",
             mem::align_of::<T>(),
@@ -612,6 +756,8 @@ mod tests {
     use std::fs;
     use std::path::PathBuf;
     use std::str::from_utf8;
+    use std::sync::Arc;
+    use std::thread;
 
     use super::*;
 
@@ -686,6 +832,492 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn huge_sizes() {
+        let sizes = Size::huge_sizes();
+        for pair in sizes.windows(2) {
+            assert!(pair[0].size(1) < pair[1].size(1));
+        }
+        if let Some(size) = Size::huge() {
+            assert!(sizes.contains(&size));
+            assert!(size.size(1) > page_size());
+        }
+    }
+
+    #[test]
+    fn alloc_huge_transparent() -> Result<()> {
+        let mut map = MapMut::with_options()
+            .huge(HugePage::Transparent)
+            .len(5)
+            .alloc()?;
+        assert_eq!(map.len(), 5);
+        map[..5].clone_from_slice(b"hello");
+        assert_eq!(Ok("hello"), from_utf8(&map[..]));
+        Ok(())
+    }
+
+    #[test]
+    fn alloc_huge_explicit() -> Result<()> {
+        let size = match Size::huge() {
+            Some(size) => size,
+            None => unsafe { Size::with_size(2 << 20) },
+        };
+        let mut opts = MapMut::with_options();
+        opts.huge(HugePage::Explicit(size));
+        for len in [Extent::Exact(5), Extent::Max(size.size(1) - 1)] {
+            let err = opts.len(len).alloc().unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        match opts.len(Extent::Min(5)).alloc() {
+            Ok(mut map) => {
+                assert_eq!(map.len(), size.size(1));
+                map[..5].clone_from_slice(b"hello");
+                map.resize(5, false)?;
+                assert_eq!(Ok("hello"), from_utf8(&map[..]));
+            }
+            Err(err) => assert_eq!(err.operation(), Operation::MapHugePage),
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn advise_extended() -> Result<()> {
+        let sz = Size::page();
+        let mut map = MapMut::with_options().len(sz.size(4)).alloc()?;
+        map[..5].clone_from_slice(b"hello");
+
+        for adv in [
+            Advise::DontDump,
+            Advise::DoDump,
+            Advise::DontFork,
+            Advise::DoFork,
+            Advise::Cold,
+            Advise::PopulateWrite,
+        ] {
+            map.advise(adv)?;
+        }
+        assert_eq!(Ok("hello"), from_utf8(&map[..5]));
+
+        let off = sz.size(2);
+        map[off..off + 5].clone_from_slice(b"world");
+        let res = map.advise_range(off, sz.size(1), Advise::Remove);
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        let ro = MapMut::with_options().len(sz.size(1)).alloc()?.into_map()?;
+        assert!(ro.advise(Advise::Free).is_err());
+        for adv in [Advise::WillNotNeed, Advise::PageOut, Advise::WipeOnFork] {
+            // These may be unsupported for the mapping, but are not rejected.
+            if let Err(err) = map.advise_range(off, sz.size(1), adv) {
+                assert!(err.raw_os_error().is_some());
+            }
+        }
+        assert_eq!(Ok("world"), from_utf8(&map[off..off + 5]));
+
+        let mut cow = MapMut::with_options().copy().len(sz.size(1)).alloc()?;
+        cow[..5].clone_from_slice(b"hello");
+        assert!(cow.advise(Advise::WillNotNeed).is_err());
+        assert_eq!(Ok("hello"), from_utf8(&cow[..5]));
+        cow.advise_destructive(Advise::WillNotNeed)?;
+        assert_eq!(Ok("\0\0\0\0\0"), from_utf8(&cow[..5]));
+
+        map.advise_destructive_range(off, sz.size(1), Advise::Remove)?;
+        assert_eq!(Ok("\0\0\0\0\0"), from_utf8(&map[off..off + 5]));
+        assert_eq!(Ok("hello"), from_utf8(&map[..5]));
+
+        let end = sz.size(4);
+        assert!(map.advise_destructive_range(end, 1, Advise::Free).is_err());
+        assert!(map.advise_destructive_range(1, end, Advise::Free).is_err());
+        assert!(map.advise_destructive_range(1, !0, Advise::Free).is_err());
+        map.advise_destructive(Advise::Remove)?;
+        assert_eq!(Ok("\0\0\0\0\0"), from_utf8(&map[..5]));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn advise_extended() -> Result<()> {
+        let map = MapMut::with_options().len(Size::page().size(1)).alloc()?;
+        let err = map.advise(Advise::DontDump).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert_eq!(err.operation(), Operation::Advise);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn resident() -> Result<()> {
+        let sz = Size::page();
+        let mut map = MapMut::with_options().len(sz.size(4)).alloc()?;
+        assert_eq!(map.resident()?, [false; 4]);
+
+        map[0] = 1;
+        map[sz.size(2) + 10] = 1;
+        assert_eq!(map.resident()?, [true, false, true, false]);
+        let range = map.resident_range(sz.size(1), sz.size(1) + 1)?;
+        assert_eq!(range, [false, true]);
+        assert!(map.resident_range(sz.size(4), 1).is_err());
+
+        let map = map.into_map()?;
+        assert_eq!(map.resident()?.iter().filter(|&&r| r).count(), 2);
+        let err = map.resident_range(sz.size(1), usize::MAX).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(map.resident_range(usize::MAX, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    fn resident_len() {
+        // More pages than fit in a u32, without mapping that much memory.
+        let page = Size::page().size(1);
+        assert_eq!(os::resident_len(0), 0);
+        assert_eq!(os::resident_len(1), 1);
+        assert_eq!(os::resident_len((page << 32) + 1), (1 << 32) + 1);
+        assert_eq!(os::resident_len(usize::MAX), usize::MAX / page + 1);
+    }
+
+    #[test]
+    fn reservation() -> Result<()> {
+        let page = Size::page().size(1);
+        let mut res = Reservation::new(page * 100)?;
+        assert_eq!(res.len(), Size::alloc().round(page * 100));
+        assert_eq!(res.committed(), 0);
+        let base = res.as_ptr();
+
+        res.commit(page * 10 + 5, page)?;
+        assert_eq!(res.committed(), page * 2);
+        assert!(res.is_committed(page * 10, page * 2));
+        assert!(!res.is_committed(page * 9, page * 2));
+        assert!(res.get(page * 9, 1).is_none());
+        res.get_mut(page * 11, 5)
+            .unwrap()
+            .clone_from_slice(b"hello");
+
+        res.commit(page * 9, page * 4)?;
+        assert_eq!(res.committed(), page * 4);
+        assert_eq!(Ok("hello"), from_utf8(res.get(page * 11, 5).unwrap()));
+
+        res.decommit(page * 11, 1)?;
+        assert_eq!(res.committed(), page * 3);
+        assert!(res.get(page * 11, 5).is_none());
+
+        res.commit(page * 11, 5)?;
+        assert_eq!(Ok("\0\0\0\0\0"), from_utf8(res.get(page * 11, 5).unwrap()));
+        assert_eq!(base, res.as_ptr());
+        assert!(res.is_committed(page * 9, page * 4));
+
+        res.decommit(page * 8, page * 2)?;
+        assert_eq!(res.committed(), page * 3);
+        assert!(res.is_committed(page * 10, page * 3));
+        assert!(!res.is_committed(page * 9, page));
+
+        assert!(res.commit(res.len(), 1).is_err());
+        assert!(res.decommit(res.len() - 1, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn reservation_large() -> Result<()> {
+        // More pages than fit in a u32. The address space may not allow it.
+        let page = Size::page().size(1);
+        let mut res = match Reservation::new((page << 32) + page) {
+            Ok(res) => res,
+            Err(_) => return Ok(()),
+        };
+        let last = res.len() - page;
+        res.commit(last, page)?;
+        assert_eq!(res.committed(), page);
+        assert!(res.is_committed(last, page));
+        assert!(!res.is_committed(0, page));
+        res.get_mut(last, 5).unwrap().clone_from_slice(b"hello");
+        res.decommit(last, page)?;
+        assert_eq!(res.committed(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn resize_shrink() -> Result<()> {
+        let sz = Size::alloc();
+        let mut map = MapMut::with_options().len(sz.size(2)).alloc()?;
+        map[..5].clone_from_slice(b"hello");
+
+        map.resize(sz.size(1) + 5, false)?;
+        assert_eq!(map.len(), sz.size(1) + 5);
+        map.resize(5, false)?;
+        assert_eq!(map.len(), 5);
+        assert_eq!(Ok("hello"), from_utf8(&map[..]));
+
+        assert!(map.resize(0, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn resize_grow() -> Result<()> {
+        let sz = Size::alloc();
+        let mut map = MapMut::with_options().copy().len(5).alloc()?;
+        map[..5].clone_from_slice(b"hello");
+
+        map.resize(sz.size(4), true)?;
+        assert_eq!(map.len(), sz.size(4));
+        assert_eq!(Ok("hello"), from_utf8(&map[..5]));
+        assert_eq!(Ok("\0\0\0\0\0"), from_utf8(&map[5..10]));
+
+        let end = map.len() - 5;
+        map[end..].clone_from_slice(b"world");
+        assert_eq!(Ok("world"), from_utf8(&map[end..]));
+        Ok(())
+    }
+
+    #[test]
+    fn resize_file() -> Result<()> {
+        let (_tmp, path, len) = write_default("resize_file")?;
+        let (mut map, file) = MapMut::with_options().offset(29).open(&path)?;
+        assert_eq!(len - 29, map.len());
+
+        map.resize_file(&file, 50, true)?;
+        assert_eq!(50, map.len());
+        assert_eq!(79, file.metadata()?.len());
+        map[38..].clone_from_slice(b" and so on..");
+        map.flush(&file, Flush::Sync)?;
+
+        let (map, _) = Map::with_options().offset(29).open(&path)?;
+        assert_eq!(
+            Ok("fast and safe memory-mapped IO in Rust and so on.."),
+            from_utf8(&map[..])
+        );
+
+        let (mut map, file) = MapMut::with_options().offset(29).open(&path)?;
+        map.resize_file(&file, 30, true)?;
+        assert_eq!(30, map.len());
+        assert_eq!(59, file.metadata()?.len());
+        assert_eq!(Ok("fast and safe memory-mapped IO"), from_utf8(&map[..]));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn resize_file_failed() -> Result<()> {
+        let sz = Size::alloc();
+        let (_tmp, path, _len) = write_default("resize_file_failed")?;
+        let file = fs::OpenOptions::new().read(true).write(true).open(&path)?;
+        file.set_len(sz.size(2) as u64)?;
+        let (mut map, file) = MapMut::with_options().open(&path)?;
+
+        // Splitting the mapping with a different protection causes mremap to
+        // reject the range.
+        let tail = unsafe { map.as_mut_ptr().add(sz.size(1)) };
+        unsafe { os::protect(tail, sz.size(1), Protect::ReadOnly)? };
+        assert!(map.resize_file(&file, sz.size(4), true).is_err());
+        assert_eq!(map.len(), sz.size(2));
+        assert_eq!(file.metadata()?.len(), sz.size(2) as u64);
+        assert_eq!(Ok("fast"), from_utf8(&map[29..33]));
+        Ok(())
+    }
+
+    #[test]
+    fn shared_map() -> Result<()> {
+        let (_tmp, path, len) = write_default("shared_map")?;
+        let (map, _) = Map::with_options().open(&path)?;
+        let shared = SharedMap::from(map);
+        assert_eq!(shared.len(), len);
+
+        let (head, tail) = shared.split_at(29);
+        assert_eq!(head.len(), 29);
+        assert_eq!(tail.offset(), 29);
+        let word = tail.slice(..=3);
+        assert_eq!(Ok("fast"), from_utf8(&word));
+        assert_eq!(word.as_ptr(), unsafe { shared.as_ptr().add(29) });
+
+        let copy = word.clone();
+        drop((shared, head, tail, word));
+        assert_eq!(Ok("fast"), from_utf8(&copy));
+        assert!(copy.slice(4..).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn shared_map_out_of_range() {
+        let map = Map::with_options().len(10).alloc().unwrap();
+        SharedMap::from(map).slice(5..11);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn shared_map_bytes() -> Result<()> {
+        let (_tmp, path, _len) = write_default("shared_map_bytes")?;
+        let (map, _) = Map::with_options().open(&path)?;
+        let ptr = map.as_ptr();
+        let bytes = bytes::Bytes::from(SharedMap::from(map).slice(29..33));
+        assert_eq!(&bytes[..], b"fast");
+        assert_eq!(bytes.as_ptr(), unsafe { ptr.add(29) });
+        let copy = bytes.slice(1..);
+        drop(bytes);
+        assert_eq!(&copy[..], b"ast");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn map_fd() -> Result<()> {
+        use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
+
+        let (_tmp, path, len) = write_default("map_fd")?;
+        let fd: OwnedFd = fs::File::open(&path)?.into();
+        let map = Map::with_options().offset(29).len(4).map_fd(&fd)?;
+        assert_eq!(Ok("fast"), from_utf8(&map[..]));
+
+        let raw = unsafe { BorrowedFd::borrow_raw(fd.as_raw_fd()) };
+        let map = Map::with_options().map_fd(raw)?;
+        assert_eq!(map.len(), len);
+        let past = Map::with_options().offset(len + 1).map_fd_if(&fd)?;
+        assert!(past.is_none());
+        drop(fd);
+        assert_eq!(Ok("fast"), from_utf8(&map[29..33]));
+
+        let file = fs::OpenOptions::new().read(true).write(true).open(&path)?;
+        let mut map = MapMut::with_options()
+            .offset(29)
+            .resize(Extent::Exact(len + 5))
+            .map_fd(file.as_fd())?;
+        assert_eq!(map.len(), len - 24);
+        map[..4].clone_from_slice(b"nice");
+        map.flush(&file, Flush::Sync)?;
+        assert_eq!(file.metadata()?.len() as usize, len + 5);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn memfd() -> Result<()> {
+        let (mut map, memfd) = MapMut::with_options()
+            .offset(10)
+            .len(Extent::Exact(30))
+            .memfd("memfd")?;
+        assert_eq!(map.len(), 30);
+        assert_eq!(memfd.file().metadata()?.len(), 40);
+        map[..4].clone_from_slice(b"fast");
+
+        let (map, _) = Map::with_options().memfd("memfd")?;
+        assert_eq!(map.len(), Size::alloc().size(1));
+        assert!(map.iter().all(|&b| b == 0));
+
+        let other = Map::with_options().offset(10).map_fd(&memfd)?;
+        assert_eq!(Ok("fast"), from_utf8(&other[..4]));
+        assert!(MemFd::open("bad\0name").is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn memfd_huge() -> Result<()> {
+        let (map, memfd) = MapMut::with_options()
+            .huge(HugePage::Transparent)
+            .len(Extent::Min(5))
+            .memfd("memfd_huge")?;
+        assert_eq!(map.len(), Size::alloc().size(1));
+        assert_eq!(memfd.file().metadata()?.len() as usize, map.len());
+
+        let size = match Size::huge() {
+            Some(size) => size,
+            None => unsafe { Size::with_size(2 << 20) },
+        };
+        let mut opts = MapMut::with_options();
+        opts.huge(HugePage::Explicit(size));
+        let err = opts.offset(1).memfd("memfd_huge").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        match opts.offset(size.size(1)).len(5).memfd("memfd_huge") {
+            Ok((mut map, memfd)) => {
+                assert_eq!(map.len(), size.size(1));
+                assert_eq!(memfd.file().metadata()?.len() as usize, size.size(2));
+                map[..5].clone_from_slice(b"hello");
+            }
+            Err(err) => assert!(matches!(
+                err.operation(),
+                Operation::MemoryFd | Operation::MapFile
+            )),
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn memfd_overflow() {
+        let res = Map::with_options().offset(usize::MAX).len(2).memfd("memfd");
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        let min = Extent::Min(usize::MAX);
+        let res = Map::with_options().len(min).memfd("memfd");
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        let res = Map::with_options().offset(1).len(usize::MAX).memfd("memfd");
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn memfd_seals() -> Result<()> {
+        let (mut map, memfd) = MapMut::with_options().len(100).memfd("memfd_seals")?;
+        assert!(memfd.seals()?.is_empty());
+        memfd.add_seals(Seals::SHRINK | Seals::GROW | Seals::FUTURE_WRITE)?;
+        assert!(memfd.set_len(50).is_err());
+        assert!(memfd.set_len(200).is_err());
+        assert!(MapMut::with_options().map_fd(&memfd).is_err());
+        map[..4].clone_from_slice(b"fast");
+
+        let required = Seals::SHRINK | Seals::GROW;
+        let other = Map::with_options().require_seals(required).map_fd(&memfd)?;
+        assert_eq!(Ok("fast"), from_utf8(&other[..4]));
+
+        let err = Map::with_options()
+            .require_seals(Seals::SEAL)
+            .map_fd(&memfd)
+            .expect_err("should require seal");
+        assert_eq!(err.operation(), Operation::MemoryFd);
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        memfd.add_seals(Seals::SEAL)?;
+        assert!(memfd.seals()?.contains(required | Seals::SEAL));
+        assert!(memfd.add_seals(Seals::WRITE).is_err());
+
+        let (_tmp, path, _len) = write_default("memfd_seals")?;
+        let res = Map::with_options().require_seals(required).open(&path);
+        assert_eq!(res.err().map(|e| e.operation()), Some(Operation::MemoryFd));
+        Ok(())
+    }
+
+    #[test]
+    fn mapped_file() -> Result<()> {
+        let (_tmp, path, _len) = write_default("mapped_file")?;
+        let (map, file) = MapMut::with_options().offset(29).open(&path)?;
+        let mut mapped = MappedFile::from((map, file));
+        assert_eq!(mapped.len(), 38);
+        assert_eq!(mapped.flush_on_drop(), None);
+
+        mapped[..4].clone_from_slice(b"nice");
+        mapped.flush_range(0, 4, Flush::Sync)?;
+        mapped.set_len(50)?;
+        assert_eq!(mapped.len(), 50);
+        assert_eq!(mapped.file().metadata()?.len(), 79);
+        mapped[38..].clone_from_slice(b" and so on..");
+        mapped.set_flush_on_drop(Some(Flush::Sync));
+        drop(mapped);
+
+        let (map, _) = Map::with_options().offset(29).open(&path)?;
+        assert_eq!(
+            Ok("nice and safe memory-mapped IO in Rust and so on.."),
+            from_utf8(&map[..])
+        );
+
+        let mut mapped = MappedFile::open(&path)?;
+        mapped.set_len(33)?;
+        mapped.sync_all()?;
+        let (map, file) = mapped.into_parts();
+        assert_eq!(map.len(), 33);
+        assert_eq!(file.metadata()?.len(), 33);
+        Ok(())
+    }
+
     #[test]
     fn read_end() -> Result<()> {
         let (_tmp, path, len) = write_default("read_end")?;
@@ -866,4 +1498,56 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Map>();
+        assert_send_sync::<MapMut>();
+    }
+
+    #[test]
+    fn share_map() -> Result<()> {
+        let (_tmp, path, _len) = write_default("share_map")?;
+        let (map, _) = Map::with_options().offset(29).len(30).open(&path)?;
+        let map = Arc::new(map);
+
+        let words = [(0, "fast"), (9, "safe"), (14, "memory"), (28, "IO")];
+        let handles = words
+            .iter()
+            .map(|&(off, word)| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    assert_eq!(Ok(word), from_utf8(&map[off..off + word.len()]));
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for h in handles {
+            h.join().expect("thread panicked");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn send_map_mut() -> Result<()> {
+        let (_tmp, path, _len) = write_default("send_map_mut")?;
+        let (map, file) = MapMut::with_options().offset(29).len(30).open(&path)?;
+
+        let mut map = thread::spawn(move || {
+            let mut map = map;
+            map[..4].clone_from_slice(b"nice");
+            map
+        })
+        .join()
+        .expect("thread panicked");
+
+        assert_eq!(Ok("nice and safe memory-mapped IO"), from_utf8(&map[..]));
+        map[..4].clone_from_slice(b"fine");
+        map.flush(&file, Flush::Sync)?;
+
+        let (map, _) = Map::with_options().offset(29).len(30).open(&path)?;
+        assert_eq!(Ok("fine and safe memory-mapped IO"), from_utf8(&map[..]));
+        Ok(())
+    }
 }
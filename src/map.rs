@@ -1,15 +1,27 @@
 use std::convert::TryFrom;
 use std::fs::{File, OpenOptions};
 use std::ops::{Deref, DerefMut};
+#[cfg(unix)]
+use std::os::unix::io::AsFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::io::BorrowedFd;
 use std::path::Path;
 use std::slice;
 use std::{cmp, fmt, io, marker};
 
-use crate::os::{advise, flush, lock, map_anon, map_file, protect, unlock, unmap};
+use crate::os::{
+    advise, flush, lock, map_anon, map_anon_huge, map_file, protect, remap, resident, unlock, unmap,
+};
+#[cfg(unix)]
+use crate::os::{fd_len, fd_set_len, map_fd};
 use crate::sealed::FromPtr;
+#[cfg(unix)]
+use crate::MemFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::Seals;
 use crate::{
-    Advise, ConvertResult, Error, Extent, Flush, Input, Operation, Protect, Result, Size, Span,
-    SpanMut,
+    Advise, ConvertResult, Error, Extent, Flush, HugePage, Input, Operation, Protect, Result, Size,
+    Span, SpanMut,
 };
 
 /// Allocation of one or more read-only sequential pages.
@@ -107,15 +119,41 @@ impl Map {
     }
 
     /// Updates the advise for the entire mapped region..
+    ///
+    /// Destructive variants of [`Advise`] are rejected.
+    ///
+    /// [`Advise`]: enum.Advise.html
     pub fn advise(&self, adv: Advise) -> Result<()> {
         self.0.advise(adv)
     }
 
     /// Updates the advise for a specific range of the mapped region.
+    ///
+    /// Destructive variants of [`Advise`] are rejected.
+    ///
+    /// [`Advise`]: enum.Advise.html
     pub fn advise_range(&self, off: usize, len: usize, adv: Advise) -> Result<()> {
         self.0.advise_range(off, len, adv)
     }
 
+    /// Queries which pages of the mapped region are resident in memory.
+    ///
+    /// See [`MapMut::resident()`] for details.
+    ///
+    /// [`MapMut::resident()`]: struct.MapMut.html#method.resident
+    pub fn resident(&self) -> Result<Vec<bool>> {
+        self.0.resident()
+    }
+
+    /// Queries which pages of a specific range are resident in memory.
+    ///
+    /// See [`MapMut::resident_range()`] for details.
+    ///
+    /// [`MapMut::resident_range()`]: struct.MapMut.html#method.resident_range
+    pub fn resident_range(&self, off: usize, len: usize) -> Result<Vec<bool>> {
+        self.0.resident_range(off, len)
+    }
+
     /// Lock all mapped physical pages into memory.
     pub fn lock(&self) -> Result<()> {
         self.0.lock()
@@ -138,8 +176,8 @@ impl Map {
 }
 
 impl FromPtr for Map {
-    unsafe fn from_ptr(ptr: *mut u8, len: usize) -> Self {
-        Self(MapMut::from_ptr(ptr, len))
+    unsafe fn from_ptr(ptr: *mut u8, len: usize, off: usize, page: Size, prot: Protect) -> Self {
+        Self(MapMut::from_ptr(ptr, len, off, page, prot))
     }
 }
 
@@ -196,8 +234,18 @@ impl fmt::Debug for Map {
 pub struct MapMut {
     ptr: *mut u8,
     len: usize,
+    off: usize,
+    page: Size,
+    private: bool,
 }
 
+// SAFETY: The mapping is exclusively owned by the `MapMut` and is released
+// only when dropped. The memory is only mutated through `&mut self`, so the
+// same rules apply as for a `Box<[u8]>`. `Map` inherits these through its
+// inner `MapMut`, and provides no mutable access at all.
+unsafe impl Send for MapMut {}
+unsafe impl Sync for MapMut {}
+
 impl MapMut {
     /// Returns a new `Options` object to create a writable `MapMut`.
     ///
@@ -320,8 +368,153 @@ impl MapMut {
         }
     }
 
+    /// Resizes the mapped region in place.
+    ///
+    /// On Linux and Android this uses `mremap` to either grow or shrink the
+    /// mapping. When `may_move` is `true`, the mapping may be relocated if it
+    /// cannot be grown at the current address, which invalidates any pointers
+    /// into the previous region. Other platforms can only shrink a mapping
+    /// using this method, and growing results in an error with a kind of
+    /// `Unsupported`.
+    ///
+    /// For file-based mappings, the new length must be available in the
+    /// underlying file. Use [`.resize_file()`] to resize the file as well.
+    /// Similarly, shared anonymous memory has a fixed size, so anonymous
+    /// allocations can only be grown when created using [`.copy()`]. A
+    /// mapping of explicit huge pages is resized in multiples of its huge
+    /// page size, which fails on systems that cannot remap huge pages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{Extent, MapMut};
+    /// use std::str::from_utf8;
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let mut map = MapMut::with_options().len(Extent::Min(100)).alloc()?;
+    /// map[..4].clone_from_slice(b"test");
+    ///
+    /// map.resize(4, false)?;
+    /// assert_eq!(4, map.len());
+    /// assert_eq!(Ok("test"), from_utf8(&map[..]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.resize_file()`]: #method.resize_file
+    /// [`.copy()`]: struct.Options.html#method.copy
+    pub fn resize(&mut self, len: usize, may_move: bool) -> Result<()> {
+        if len == 0 {
+            return Err(Error::input(Operation::Remap, Input::InvalidRange));
+        }
+        unsafe {
+            let size = self.page;
+            let (ptr, old) = size.bounds(self.ptr, self.len);
+            let off = self.ptr as usize - ptr as usize;
+            let new = size.round(off + len);
+            if new != old {
+                self.ptr = remap(ptr, old, new, may_move)?.add(off);
+            }
+        }
+        self.len = len;
+        Ok(())
+    }
+
+    /// Resizes both the underlying file and the mapped region.
+    ///
+    /// The file is resized to end at the new end of the mapping. The file is
+    /// grown prior to growing the mapping, and it is truncated after shrinking
+    /// the mapping, and if the mapping cannot be grown, the file is restored to
+    /// its original length. See [`.resize()`] for details on `may_move`. On platforms
+    /// without `mremap`, when `may_move` is `true`, the file is instead mapped
+    /// again at the new length and the previous region is unmapped.
+    ///
+    /// The `file` must be the same file used to create the mapping, and it
+    /// must be opened with write access.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::MapMut;
+    /// use std::path::PathBuf;
+    /// use std::str::from_utf8;
+    /// # use std::fs;
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// # let tmp = tempdir::TempDir::new("vmap")?;
+    /// let path: PathBuf = /* path to file */
+    /// # tmp.path().join("example");
+    /// # fs::write(&path, b"this is a test")?;
+    /// let (mut map, file) = MapMut::with_options().offset(5).open(&path)?;
+    /// assert_eq!(Ok("is a test"), from_utf8(&map[..]));
+    ///
+    /// map.resize_file(&file, 17, true)?;
+    /// map[9..].clone_from_slice(b" of vmap");
+    /// assert_eq!(22, file.metadata()?.len());
+    /// assert_eq!(Ok("is a test of vmap"), from_utf8(&map[..]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.resize()`]: #method.resize
+    pub fn resize_file(&mut self, file: &File, len: usize, may_move: bool) -> Result<()> {
+        if len == 0 {
+            return Err(Error::input(Operation::Remap, Input::InvalidRange));
+        }
+        let old = self.len;
+        let remap_err = |e| Error::io(Operation::Remap, e);
+        let set_len = |flen: u64| file.set_len(flen).map_err(remap_err);
+        let grow_from = if len > old {
+            let flen = file.metadata().map_err(remap_err)?.len();
+            set_len((self.off + len) as u64)?;
+            Some(flen)
+        } else {
+            None
+        };
+        let res = match self.resize(len, may_move) {
+            Err(err) if may_move && err.kind() == io::ErrorKind::Unsupported => {
+                self.remap_file(file, len)
+            }
+            res => res,
+        };
+        if let Err(err) = res {
+            // Restore the original length so the file still matches the
+            // unchanged mapping.
+            if let Some(flen) = grow_from {
+                set_len(flen).unwrap_or_default();
+            }
+            return Err(err);
+        }
+        if len < old {
+            set_len((self.off + len) as u64)?;
+        }
+        Ok(())
+    }
+
+    fn remap_file(&mut self, file: &File, len: usize) -> Result<()> {
+        let mapoff = Size::alloc().truncate(self.off);
+        let ptr = map_file(file, mapoff, len + (self.off - mapoff), Protect::ReadWrite)?;
+        unsafe {
+            let (old, oldlen) = self.page.bounds(self.ptr, self.len);
+            unmap(old, oldlen).unwrap_or_default();
+            self.ptr = ptr.add(self.off - mapoff);
+        }
+        self.len = len;
+        Ok(())
+    }
+
     /// Updates the advise for the entire mapped region..
+    ///
+    /// Destructive variants of [`Advise`] are rejected, as they could modify
+    /// memory that is currently borrowed. Use [`.advise_destructive()`]
+    /// instead.
+    ///
+    /// [`Advise`]: enum.Advise.html
+    /// [`.advise_destructive()`]: #method.advise_destructive
     pub fn advise(&self, adv: Advise) -> Result<()> {
+        if self.is_destructive(adv) {
+            return Err(Error::input(Operation::Advise, Input::DestructiveAdvise));
+        }
         unsafe {
             let (ptr, len) = Size::page().bounds(self.ptr, self.len);
             advise(ptr, len, adv)
@@ -329,7 +522,17 @@ impl MapMut {
     }
 
     /// Updates the advise for a specific range of the mapped region.
+    ///
+    /// Destructive variants of [`Advise`] are rejected, as they could modify
+    /// memory that is currently borrowed. Use [`.advise_destructive_range()`]
+    /// instead.
+    ///
+    /// [`Advise`]: enum.Advise.html
+    /// [`.advise_destructive_range()`]: #method.advise_destructive_range
     pub fn advise_range(&self, off: usize, len: usize, adv: Advise) -> Result<()> {
+        if self.is_destructive(adv) {
+            return Err(Error::input(Operation::Advise, Input::DestructiveAdvise));
+        }
         if off + len > self.len {
             Err(Error::input(Operation::Advise, Input::InvalidRange))
         } else {
@@ -340,6 +543,109 @@ impl MapMut {
         }
     }
 
+    /// Updates the advise for the entire mapped region, including destructive
+    /// variants.
+    ///
+    /// This accepts any [`Advise`]. Destructive variants such as
+    /// `Advise::Remove` may zero or discard the contents, which requires
+    /// exclusive access to the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(any(target_os = "linux", target_os = "android"))]
+    /// # fn main() -> vmap::Result<()> {
+    /// use vmap::{Advise, MapMut};
+    ///
+    /// let mut map = MapMut::new(4096)?;
+    /// map[..4].clone_from_slice(b"test");
+    /// assert!(map.advise(Advise::Remove).is_err());
+    /// map.advise_destructive(Advise::Remove)?;
+    /// assert_eq!(b"\0\0\0\0", &map[..4]);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    /// # fn main() {}
+    /// ```
+    ///
+    /// [`Advise`]: enum.Advise.html
+    pub fn advise_destructive(&mut self, adv: Advise) -> Result<()> {
+        unsafe {
+            let (ptr, len) = Size::page().bounds(self.ptr, self.len);
+            advise(ptr, len, adv)
+        }
+    }
+
+    /// Updates the advise for a specific range of the mapped region, including
+    /// destructive variants.
+    ///
+    /// Every page that overlaps the range is affected. See
+    /// [`.advise_destructive()`] for details.
+    ///
+    /// [`.advise_destructive()`]: #method.advise_destructive
+    pub fn advise_destructive_range(&mut self, off: usize, len: usize, adv: Advise) -> Result<()> {
+        if off.checked_add(len).is_none_or(|end| end > self.len) {
+            Err(Error::input(Operation::Advise, Input::InvalidRange))
+        } else {
+            unsafe {
+                let (ptr, len) = Size::page().bounds(self.ptr.add(off), len);
+                advise(ptr, len, adv)
+            }
+        }
+    }
+
+    /// Tests if the advise may discard the contents of this mapping.
+    fn is_destructive(&self, adv: Advise) -> bool {
+        adv.is_destructive() || (self.private && adv == Advise::WillNotNeed)
+    }
+
+    /// Queries which pages of the mapped region are resident in memory.
+    ///
+    /// The result contains an entry for each page covered by the mapping,
+    /// which is `true` when that page is resident in physical memory. This
+    /// is only a snapshot, as pages may be loaded or evicted at any time.
+    /// Platforms without residency queries result in an error with a kind
+    /// of `Unsupported`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{Advise, Map};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let (map, _) = Map::with_options().open("README.md")?;
+    /// let pages = map.resident()?;
+    /// let count = pages.iter().filter(|&&r| r).count();
+    /// if count < pages.len() {
+    ///     map.advise(Advise::WillNeed)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resident(&self) -> Result<Vec<bool>> {
+        unsafe {
+            let (ptr, len) = Size::page().bounds(self.ptr, self.len);
+            resident(ptr, len)
+        }
+    }
+
+    /// Queries which pages of a specific range are resident in memory.
+    ///
+    /// The result contains an entry for each page that overlaps the range.
+    /// See [`.resident()`] for details.
+    ///
+    /// [`.resident()`]: #method.resident
+    pub fn resident_range(&self, off: usize, len: usize) -> Result<Vec<bool>> {
+        if off.checked_add(len).is_none_or(|end| end > self.len) {
+            Err(Error::input(Operation::Resident, Input::InvalidRange))
+        } else {
+            unsafe {
+                let (ptr, len) = Size::page().bounds(self.ptr.add(off), len);
+                resident(ptr, len)
+            }
+        }
+    }
+
     /// Lock all mapped physical pages into memory.
     pub fn lock(&self) -> Result<()> {
         unsafe {
@@ -382,8 +688,14 @@ impl MapMut {
 }
 
 impl FromPtr for MapMut {
-    unsafe fn from_ptr(ptr: *mut u8, len: usize) -> Self {
-        Self { ptr, len }
+    unsafe fn from_ptr(ptr: *mut u8, len: usize, off: usize, page: Size, prot: Protect) -> Self {
+        Self {
+            ptr,
+            len,
+            off,
+            page,
+            private: matches!(prot, Protect::ReadCopy | Protect::ReadExec),
+        }
     }
 }
 
@@ -410,7 +722,7 @@ impl Drop for MapMut {
     fn drop(&mut self) {
         unsafe {
             if self.len > 0 {
-                let (ptr, len) = Size::alloc().bounds(self.ptr, self.len);
+                let (ptr, len) = self.page.bounds(self.ptr, self.len);
                 unmap(ptr, len).unwrap_or_default();
             }
         }
@@ -502,6 +814,9 @@ pub struct Options<T: FromPtr> {
     offset: usize,
     protect: Protect,
     truncate: bool,
+    huge: Option<HugePage>,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    seals: Seals,
     _marker: marker::PhantomData<fn() -> T>,
 }
 
@@ -524,6 +839,9 @@ impl<T: FromPtr> Options<T> {
             offset: 0,
             protect: Protect::ReadOnly,
             truncate: false,
+            huge: None,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            seals: Seals::empty(),
             _marker: marker::PhantomData,
         }
     }
@@ -1032,6 +1350,102 @@ impl<T: FromPtr> Options<T> {
         self
     }
 
+    /// Sets the huge page strategy for an anonymous allocation.
+    ///
+    /// Using huge pages reduces the number of TLB entries needed to cover
+    /// large allocations. This only applies when using [`.alloc()`].
+    ///
+    /// With [`HugePage::Transparent`], the allocation is advised to be backed
+    /// by transparent huge pages. This is a best-effort hint, and the
+    /// allocation succeeds using normal pages when they are unavailable.
+    ///
+    /// With [`HugePage::Explicit`], the allocation is taken from the system's
+    /// reserved huge page pool. Because huge pages cannot be partially
+    /// unmapped, the allocation must end on a huge page boundary. A length of
+    /// [`Extent::Min`] or [`Extent::End`] is rounded up to the boundary, and
+    /// [`Extent::Max`] is rounded down. An [`Extent::Exact`] length that does
+    /// not end on the boundary, or an [`Extent::Max`] that is smaller than
+    /// one huge page, fails with [`Input::InvalidRange`]. If no huge pages
+    /// are available, the allocation fails with an [`Error`] whose operation
+    /// is [`Operation::MapHugePage`]. This allows the caller to fall back to
+    /// a normal allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{Extent, HugePage, MapMut, Operation, Size};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let huge = Size::huge().map(HugePage::Explicit).unwrap_or(HugePage::Transparent);
+    /// let map = match MapMut::with_options().huge(huge).len(Extent::Min(1 << 20)).alloc() {
+    ///     Err(err) if err.operation() == Operation::MapHugePage => {
+    ///         MapMut::with_options().huge(HugePage::Transparent).len(Extent::Min(1 << 20)).alloc()?
+    ///     }
+    ///     res => res?,
+    /// };
+    /// assert!(map.len() >= 1 << 20);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.alloc()`]: #method.alloc
+    /// [`HugePage::Transparent`]: enum.HugePage.html#variant.Transparent
+    /// [`HugePage::Explicit`]: enum.HugePage.html#variant.Explicit
+    /// [`Extent::Min`]: enum.Extent.html#variant.Min
+    /// [`Extent::End`]: enum.Extent.html#variant.End
+    /// [`Extent::Max`]: enum.Extent.html#variant.Max
+    /// [`Extent::Exact`]: enum.Extent.html#variant.Exact
+    /// [`Input::InvalidRange`]: enum.Input.html#variant.InvalidRange
+    /// [`Error`]: struct.Error.html
+    /// [`Operation::MapHugePage`]: enum.Operation.html#variant.MapHugePage
+    pub fn huge(&mut self, huge: HugePage) -> &mut Self {
+        self.huge = Some(huge);
+        self
+    }
+
+    /// Sets the seals that a mapped descriptor is required to have.
+    ///
+    /// This protects against a descriptor received from an untrusted process,
+    /// such as through [`recv_fd`], being modified in ways that could fault
+    /// access to the mapping. For example, requiring [`Seals::SHRINK`] ensures
+    /// the mapped range cannot be truncated. This applies when using
+    /// [`.map()`] and [`.map_fd()`], as well as the variants of these. When
+    /// any seals are required, mapping a descriptor that is missing one of
+    /// them, or that does not support sealing, fails with an [`Error`] whose
+    /// operation is [`Operation::MemoryFd`].
+    ///
+    /// These options are not used when attaching to a shared ring buffer. Use
+    /// [`SharedRing::from_fd_sealed()`] to require seals for those instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{Map, MapMut, Seals};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let (_map, memfd) = MapMut::with_options().len(4096).memfd("example")?;
+    /// let seals = Seals::SHRINK | Seals::GROW;
+    /// assert!(Map::with_options().require_seals(seals).map_fd(&memfd).is_err());
+    ///
+    /// memfd.add_seals(seals)?;
+    /// assert!(Map::with_options().require_seals(seals).map_fd(&memfd).is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`recv_fd`]: os/fn.recv_fd.html
+    /// [`Seals::SHRINK`]: struct.Seals.html#associatedconstant.SHRINK
+    /// [`.map()`]: #method.map
+    /// [`.map_fd()`]: #method.map_fd
+    /// [`Error`]: struct.Error.html
+    /// [`Operation::MemoryFd`]: enum.Operation.html#variant.MemoryFd
+    /// [`SharedRing::from_fd_sealed()`]: io/struct.SharedRing.html#method.from_fd_sealed
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn require_seals(&mut self, seals: Seals) -> &mut Self {
+        self.seals = seals;
+        self
+    }
+
     /// Opens and maps a file using the current options specified by `self`.
     ///
     /// Unlike [`.open_if()`], when the requested offset or length lies outside of
@@ -1180,10 +1594,183 @@ impl<T: FromPtr> Options<T> {
     ///
     /// [`.map()`]: #method.map
     pub fn map_if(&self, f: &File) -> Result<Option<T>> {
-        let off = self.offset;
-        let mut flen = f.metadata().map_err(map_file_err)?.len() as usize;
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.check_seals(f.as_fd())?;
 
+        let flen = f.metadata().map_err(map_file_err)?.len() as usize;
         let resize = |sz: usize| f.set_len(sz as u64).map(|_| sz).map_err(map_file_err);
+        self.map_range(flen, resize, |off, len| map_file(f, off, len, self.protect))
+    }
+
+    /// Maps a file descriptor with the options specified by `self`.
+    ///
+    /// This accepts anything that can lend out a file descriptor, such as a
+    /// `File`, an `OwnedFd`, or a `BorrowedFd`. A `RawFd` may be used through
+    /// `BorrowedFd::borrow_raw`. The size of the underlying object is queried
+    /// using `fstat`, and a resize is applied with `ftruncate`. The mapping
+    /// remains valid after the descriptor is closed.
+    ///
+    /// Unlike [`.map_fd_if()`], when the requested offset or length lies
+    /// outside of the underlying object, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::Map;
+    /// use std::os::unix::io::OwnedFd;
+    /// use std::path::PathBuf;
+    /// # use std::fs::{self, File};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// # let tmp = tempdir::TempDir::new("vmap")?;
+    /// let path: PathBuf = /* path to file */
+    /// # tmp.path().join("example");
+    /// # fs::write(&path, b"this is a test")?;
+    /// let fd: OwnedFd = File::open(&path)?.into();
+    /// let map = Map::with_options().offset(10).map_fd(&fd)?;
+    /// assert_eq!(b"test", &map[..]);
+    /// assert!(Map::with_options().len(25).map_fd(&fd).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.map_fd_if()`]: #method.map_fd_if
+    #[cfg(unix)]
+    pub fn map_fd<F: AsFd>(&self, fd: F) -> Result<T> {
+        self.map_fd_if(fd)?
+            .ok_or_else(|| Error::input(Operation::MapFile, Input::InvalidRange))
+    }
+
+    /// Maps a file descriptor with the options specified by `self` if the
+    /// provided byte range is valid.
+    ///
+    /// Unlike [`.map_fd()`], when the requested offset or length lies outside
+    /// of the underlying object, `Ok(None)` will be returned rather than an
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::Map;
+    /// use std::os::unix::io::AsFd;
+    /// use std::path::PathBuf;
+    /// # use std::fs::{self, File};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// # let tmp = tempdir::TempDir::new("vmap")?;
+    /// let path: PathBuf = /* path to file */
+    /// # tmp.path().join("example");
+    /// # fs::write(&path, b"this is a test")?;
+    /// let f = File::open(&path)?;
+    /// assert!(Map::with_options().len(4).map_fd_if(f.as_fd())?.is_some());
+    /// assert!(Map::with_options().len(25).map_fd_if(f.as_fd())?.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`.map_fd()`]: #method.map_fd
+    #[cfg(unix)]
+    pub fn map_fd_if<F: AsFd>(&self, fd: F) -> Result<Option<T>> {
+        let fd = fd.as_fd();
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.check_seals(fd)?;
+
+        let flen = fd_len(fd)?;
+        let resize = |sz: usize| fd_set_len(fd, sz).map(|_| sz);
+        self.map_range(flen, resize, |off, len| map_fd(fd, off, len, self.protect))
+    }
+
+    /// Creates a shareable anonymous allocation using the options specified by
+    /// `self`.
+    ///
+    /// This creates a new [`MemFd`] using `name`, sizes it to cover the
+    /// offset and length, and maps it. The resize and truncate options are not
+    /// used. As with [`.alloc()`], a length of `Extent::End` or `Extent::Min`
+    /// is rounded up to the allocation size, and the [`.huge()`] strategy is
+    /// applied. With `HugePage::Explicit`, which is only supported on Linux,
+    /// the offset must be a multiple of the huge page size, and every length
+    /// is rounded up to it. Unlike [`.alloc()`], the returned [`MemFd`] can be
+    /// used to map the same memory again, either in this process or another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{Extent, Map, MapMut};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let (mut map, memfd) = MapMut::with_options()
+    ///     .len(Extent::Min(500))
+    ///     .memfd("example")?;
+    /// assert!(map.len() >= 500);
+    /// map[..4].clone_from_slice(b"test");
+    ///
+    /// let other = Map::with_options().len(4).map_fd(&memfd)?;
+    /// assert_eq!(b"test", &other[..]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`MemFd`]: struct.MemFd.html
+    /// [`.alloc()`]: #method.alloc
+    /// [`.huge()`]: #method.huge
+    #[cfg(unix)]
+    pub fn memfd(&self, name: &str) -> Result<(T, MemFd)> {
+        let off = self.offset;
+        let (size, explicit) = match self.huge {
+            Some(HugePage::Explicit(size)) => (size, true),
+            _ => (Size::alloc(), false),
+        };
+        let invalid = || Error::input(Operation::MemoryFd, Input::InvalidRange);
+        if explicit && size.offset(off) != 0 {
+            return Err(invalid());
+        }
+
+        let round = |end: usize| end.checked_add(size.size(1) - 1).map(|n| size.truncate(n));
+        let end = match self.len {
+            Extent::Max(l) | Extent::Exact(l) if !explicit => off.checked_add(l),
+            Extent::End => off.checked_add(1).and_then(round),
+            Extent::Min(l) | Extent::Max(l) | Extent::Exact(l) => {
+                off.checked_add(l).and_then(round)
+            }
+        }
+        .ok_or_else(invalid)?;
+        let len = end - off;
+
+        let memfd = if explicit {
+            MemFd::open_huge(name, size)?
+        } else {
+            MemFd::open(name)?
+        };
+        memfd.set_len(end)?;
+
+        let mapoff = size.truncate(off);
+        let maplen = len + (off - mapoff);
+        let ptr = map_fd(memfd.as_fd(), mapoff, maplen, self.protect)?;
+        if let Some(HugePage::Transparent) = self.huge {
+            unsafe { advise(ptr, maplen, Advise::HugePage) }.unwrap_or_default();
+        }
+        let map = unsafe { T::from_ptr(ptr.add(off - mapoff), len, off, size, self.protect) };
+        Ok((map, memfd))
+    }
+
+    /// Verifies that a descriptor has all of the required seals.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn check_seals(&self, fd: BorrowedFd<'_>) -> Result<()> {
+        if self.seals.is_empty() || Seals::of(fd)?.contains(self.seals) {
+            Ok(())
+        } else {
+            Err(Error::input(Operation::MemoryFd, Input::MissingSeals))
+        }
+    }
+
+    /// Applies the resize and range options to an object of length `flen`,
+    /// then maps the resulting range.
+    fn map_range<R, M>(&self, mut flen: usize, resize: R, map: M) -> Result<Option<T>>
+    where
+        R: Fn(usize) -> Result<usize>,
+        M: FnOnce(usize, usize) -> Result<*mut u8>,
+    {
+        let off = self.offset;
 
         if self.truncate && flen > 0 {
             flen = resize(0)?;
@@ -1208,10 +1795,12 @@ impl<T: FromPtr> Options<T> {
             Extent::Exact(l) => l,
         };
 
-        let mapoff = Size::alloc().truncate(off);
+        let size = Size::alloc();
+        let mapoff = size.truncate(off);
         let maplen = len + (off - mapoff);
-        let ptr = map_file(f, mapoff, maplen, self.protect)?;
-        unsafe { Ok(Some(T::from_ptr(ptr.add(off - mapoff), len))) }
+        let ptr = map(mapoff, maplen)?;
+        let map = unsafe { T::from_ptr(ptr.add(off - mapoff), len, off, size, self.protect) };
+        Ok(Some(map))
     }
 
     /// Creates an anonymous allocation using the options specified by `self`.
@@ -1229,14 +1818,26 @@ impl<T: FromPtr> Options<T> {
     /// ```
     pub fn alloc(&self) -> Result<T> {
         let off = Size::page().offset(self.offset);
+
+        if let Some(HugePage::Explicit(size)) = self.huge {
+            let end = huge_end(size, off, &self.len)
+                .ok_or_else(|| Error::input(Operation::MapHugePage, Input::InvalidRange))?;
+            let ptr = map_anon_huge(end, self.protect, size.size(1))?;
+            return unsafe { Ok(T::from_ptr(ptr.add(off), end - off, 0, size, self.protect)) };
+        }
+
+        let size = Size::alloc();
         let len = match self.len {
-            Extent::End => Size::alloc().round(off + 1) - off,
-            Extent::Min(l) => Size::alloc().round(off + l) - off,
+            Extent::End => size.round(off + 1) - off,
+            Extent::Min(l) => size.round(off + l) - off,
             Extent::Max(l) | Extent::Exact(l) => l,
         };
 
         let ptr = map_anon(off + len, self.protect)?;
-        unsafe { Ok(T::from_ptr(ptr.add(off), len)) }
+        if let Some(HugePage::Transparent) = self.huge {
+            unsafe { advise(ptr, off + len, Advise::HugePage) }.unwrap_or_default();
+        }
+        unsafe { Ok(T::from_ptr(ptr.add(off), len, 0, size, self.protect)) }
     }
 }
 
@@ -1246,6 +1847,20 @@ impl<T: FromPtr> Default for Options<T> {
     }
 }
 
+/// Calculates the end of a range of explicit huge pages of `size` starting at
+/// `off`. Huge pages cannot be partially mapped, so the end must be on a huge
+/// page boundary.
+fn huge_end(size: Size, off: usize, len: &Extent) -> Option<usize> {
+    let round = |end: usize| end.checked_add(size.size(1) - 1).map(|n| size.truncate(n));
+    match *len {
+        Extent::End => off.checked_add(1).and_then(round),
+        Extent::Min(l) => off.checked_add(l).and_then(round),
+        Extent::Max(l) => off.checked_add(l).map(|n| size.truncate(n)),
+        Extent::Exact(l) => off.checked_add(l).filter(|&n| size.offset(n) == 0),
+    }
+    .filter(|&end| end > off)
+}
+
 fn map_file_err(e: io::Error) -> Error {
     Error::io(Operation::MapFile, e)
 }
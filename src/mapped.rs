@@ -0,0 +1,214 @@
+use std::fs::File;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::ptr;
+
+use crate::{Error, Flush, MapMut, Operation, Result, Span, SpanMut};
+
+/// Writable file mapping that owns both the mapping and its `File`.
+///
+/// The [`MapMut`] type intentionally never retains the `File` it was mapped
+/// from, so operations like [`MapMut::flush()`] need it passed back in each
+/// time. The `MappedFile` bundles the two together for the cases where that
+/// bookkeeping is undesirable, such as when passing a mapping around.
+///
+/// Optionally, the mapping may be flushed when the `MappedFile` is dropped
+/// by setting [`.set_flush_on_drop()`].
+///
+/// # Examples
+///
+/// ```
+/// use vmap::{Flush, MappedFile};
+/// use std::path::PathBuf;
+/// use std::str::from_utf8;
+/// # use std::fs;
+///
+/// # fn main() -> vmap::Result<()> {
+/// # let tmp = tempdir::TempDir::new("vmap")?;
+/// let path: PathBuf = /* path to file */
+/// # tmp.path().join("example");
+/// # fs::write(&path, b"this is a test")?;
+/// {
+///     let mut file = MappedFile::open(&path)?;
+///     file.set_flush_on_drop(Some(Flush::Sync));
+///     file[..4].clone_from_slice(b"that");
+///     file.set_len(22)?;
+///     file[14..].clone_from_slice(b" of vmap");
+/// }
+///
+/// assert_eq!(b"that is a test of vmap", &fs::read(&path)?[..]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`MapMut`]: struct.MapMut.html
+/// [`MapMut::flush()`]: struct.MapMut.html#method.flush
+/// [`.set_flush_on_drop()`]: #method.set_flush_on_drop
+#[derive(Debug)]
+pub struct MappedFile {
+    map: MapMut,
+    file: File,
+    flush_on_drop: Option<Flush>,
+}
+
+impl MappedFile {
+    /// Creates a new `MappedFile` from a mapping and the file it maps.
+    ///
+    /// The `file` must be the same file used to create the mapping. It must
+    /// also be opened with write access in order to use [`.set_len()`].
+    ///
+    /// [`.set_len()`]: #method.set_len
+    pub fn new(map: MapMut, file: File) -> Self {
+        Self {
+            map,
+            file,
+            flush_on_drop: None,
+        }
+    }
+
+    /// Opens and maps an entire file for reading and writing.
+    ///
+    /// Use [`MapMut::with_options()`] and [`MappedFile::new()`] for more
+    /// control over how the file is mapped.
+    ///
+    /// [`MapMut::with_options()`]: struct.MapMut.html#method.with_options
+    /// [`MappedFile::new()`]: #method.new
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let (map, file) = MapMut::with_options().open(path)?;
+        Ok(Self::new(map, file))
+    }
+
+    /// Consumes the `MappedFile`, returning the mapping and the file.
+    ///
+    /// No flush is performed, regardless of the flush-on-drop policy.
+    pub fn into_parts(self) -> (MapMut, File) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.map), ptr::read(&this.file)) }
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Gets a reference to the underlying mapping.
+    pub fn map(&self) -> &MapMut {
+        &self.map
+    }
+
+    /// Gets a mutable reference to the underlying mapping.
+    pub fn map_mut(&mut self) -> &mut MapMut {
+        &mut self.map
+    }
+
+    /// Gets the flush-on-drop policy.
+    pub fn flush_on_drop(&self) -> Option<Flush> {
+        self.flush_on_drop
+    }
+
+    /// Sets the flush-on-drop policy.
+    ///
+    /// When set, the entire mapping is flushed using the mode when the
+    /// `MappedFile` is dropped. Any error from this flush is ignored, so an
+    /// explicit [`.flush()`] should be preferred when errors must be handled.
+    ///
+    /// [`.flush()`]: #method.flush
+    pub fn set_flush_on_drop(&mut self, mode: Option<Flush>) {
+        self.flush_on_drop = mode;
+    }
+
+    /// Writes modifications back to the filesystem.
+    pub fn flush(&self, mode: Flush) -> Result<()> {
+        self.map.flush(&self.file, mode)
+    }
+
+    /// Writes modifications back to the filesystem for a sub-range of the map.
+    pub fn flush_range(&self, off: usize, len: usize, mode: Flush) -> Result<()> {
+        self.map.flush_range(&self.file, off, len, mode)
+    }
+
+    /// Writes all modifications and file metadata to the filesystem.
+    ///
+    /// This synchronously flushes the mapping and then calls `sync_all` on
+    /// the file.
+    pub fn sync_all(&self) -> Result<()> {
+        self.flush(Flush::Sync)?;
+        self.file
+            .sync_all()
+            .map_err(|e| Error::io(Operation::Flush, e))
+    }
+
+    /// Resizes both the underlying file and the mapping.
+    ///
+    /// See [`MapMut::resize_file()`] for details. The mapping may move to a
+    /// new address, which invalidates any pointers into the previous region.
+    ///
+    /// [`MapMut::resize_file()`]: struct.MapMut.html#method.resize_file
+    pub fn set_len(&mut self, len: usize) -> Result<()> {
+        self.map.resize_file(&self.file, len, true)
+    }
+}
+
+impl From<(MapMut, File)> for MappedFile {
+    fn from((map, file): (MapMut, File)) -> Self {
+        Self::new(map, file)
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if let Some(mode) = self.flush_on_drop {
+            self.flush(mode).unwrap_or_default();
+        }
+    }
+}
+
+impl Span for MappedFile {
+    #[inline]
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *const u8 {
+        self.map.as_ptr()
+    }
+}
+
+impl SpanMut for MappedFile {
+    #[inline]
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.map.as_mut_ptr()
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl DerefMut for MappedFile {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.deref()
+    }
+}
+
+impl AsMut<[u8]> for MappedFile {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.deref_mut()
+    }
+}
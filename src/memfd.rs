@@ -0,0 +1,261 @@
+use std::ffi::CString;
+use std::fs::File;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::ops::{BitOr, BitOrAssign};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::raw::c_int;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::os::{memfd_add_seals, memfd_get_seals};
+use crate::os::{memfd_open, memfd_open_huge};
+use crate::{Error, Operation, Result, Size};
+
+/// Anonymous memory file descriptor that may be shared between processes.
+///
+/// A `MemFd` refers to memory that is not backed by any file system path, but
+/// unlike an anonymous allocation, it can be mapped again later or passed to
+/// another process. This may happen either by inheriting the descriptor in a
+/// child process, or by sending it over a Unix domain socket. Each mapping of
+/// the descriptor using `MAP_SHARED` refers to the same pages.
+///
+/// The typical way to create a `MemFd` is along with a mapping using
+/// [`Options::memfd()`].
+///
+/// # Examples
+///
+/// ```
+/// use vmap::{Map, MapMut};
+///
+/// # fn main() -> vmap::Result<()> {
+/// let (mut map, memfd) = MapMut::with_options().len(4096).memfd("example")?;
+/// map[..4].clone_from_slice(b"test");
+///
+/// let other = Map::with_options().len(4).map_fd(&memfd)?;
+/// assert_eq!(b"test", &other[..]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Options::memfd()`]: struct.Options.html#method.memfd
+#[derive(Debug)]
+pub struct MemFd {
+    file: File,
+}
+
+impl MemFd {
+    /// Creates a new empty `MemFd`.
+    ///
+    /// The `name` is only used for debugging, such as in the link name shown
+    /// in `/proc/self/fd` on Linux, and is ignored on targets that do not
+    /// support naming the descriptor. The descriptor has the close-on-exec
+    /// flag set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::MemFd;
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let memfd = MemFd::open("example")?;
+    /// assert_eq!(memfd.file().metadata()?.len(), 0);
+    /// memfd.set_len(100)?;
+    /// assert_eq!(memfd.file().metadata()?.len(), 100);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open(name: &str) -> Result<Self> {
+        let fd = memfd_open(&Self::c_name(name)?)?;
+        Ok(Self {
+            file: unsafe { File::from_raw_fd(fd) },
+        })
+    }
+
+    /// Creates a new empty `MemFd` using explicit huge pages of `size`.
+    pub(crate) fn open_huge(name: &str, size: Size) -> Result<Self> {
+        let fd = memfd_open_huge(&Self::c_name(name)?, size.size(1))?;
+        Ok(Self {
+            file: unsafe { File::from_raw_fd(fd) },
+        })
+    }
+
+    fn c_name(name: &str) -> Result<CString> {
+        CString::new(name).map_err(|e| Error::io(Operation::MemoryFd, e.into()))
+    }
+
+    /// Sets the size of the underlying memory.
+    ///
+    /// Growing the size fills the new range with zeros. Shrinking the size
+    /// while a mapping still covers the removed range causes an access of
+    /// that range to fail with `SIGBUS`.
+    pub fn set_len(&self, len: usize) -> Result<()> {
+        self.file
+            .set_len(len as u64)
+            .map_err(|e| Error::io(Operation::MemoryFd, e))
+    }
+
+    /// Adds seals to restrict further changes to the memory.
+    ///
+    /// Seals can never be removed, so a process that receives the descriptor
+    /// can rely on them. For example, [`Seals::SHRINK`] guarantees that the
+    /// size cannot be reduced beneath an existing mapping. Adding
+    /// [`Seals::WRITE`] fails while any writable shared mapping exists, but
+    /// [`Seals::FUTURE_WRITE`] only prevents new writable mappings and writes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vmap::{MapMut, Seals};
+    ///
+    /// # fn main() -> vmap::Result<()> {
+    /// let (_map, memfd) = MapMut::with_options().len(4096).memfd("example")?;
+    /// memfd.add_seals(Seals::SHRINK | Seals::GROW | Seals::SEAL)?;
+    /// assert!(memfd.set_len(100).is_err());
+    /// assert!(memfd.add_seals(Seals::WRITE).is_err());
+    /// assert!(memfd.seals()?.contains(Seals::SHRINK | Seals::GROW));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Seals::SHRINK`]: struct.Seals.html#associatedconstant.SHRINK
+    /// [`Seals::WRITE`]: struct.Seals.html#associatedconstant.WRITE
+    /// [`Seals::FUTURE_WRITE`]: struct.Seals.html#associatedconstant.FUTURE_WRITE
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn add_seals(&self, seals: Seals) -> Result<()> {
+        seals.add_to(self.as_fd())
+    }
+
+    /// Gets the seals currently applied to the memory.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn seals(&self) -> Result<Seals> {
+        Seals::of(self)
+    }
+
+    /// Gets a reference to the descriptor as a `File`.
+    ///
+    /// This is useful for operations that require a `File`, such as
+    /// [`MapMut::flush()`].
+    ///
+    /// [`MapMut::flush()`]: struct.MapMut.html#method.flush
+    #[inline]
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Unwraps this `MemFd`, returning the descriptor as a `File`.
+    #[inline]
+    pub fn into_file(self) -> File {
+        self.file
+    }
+}
+
+impl From<MemFd> for File {
+    fn from(memfd: MemFd) -> Self {
+        memfd.file
+    }
+}
+
+impl From<MemFd> for OwnedFd {
+    fn from(memfd: MemFd) -> Self {
+        memfd.file.into()
+    }
+}
+
+impl AsFd for MemFd {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl AsRawFd for MemFd {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for MemFd {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        self.file.into_raw_fd()
+    }
+}
+
+/// Set of seals restricting changes to a [`MemFd`].
+///
+/// Seals are combined using the `|` operator. A receiving process may require
+/// seals before mapping a descriptor using [`Options::require_seals()`], or
+/// before attaching to a shared ring using [`SharedRing::from_fd_sealed()`].
+///
+/// Sealing is only supported on Linux and Android. Other targets, including
+/// FreeBSD, do not provide this type or the methods that use it.
+///
+/// [`MemFd`]: struct.MemFd.html
+/// [`Options::require_seals()`]: struct.Options.html#method.require_seals
+/// [`SharedRing::from_fd_sealed()`]: io/struct.SharedRing.html#method.from_fd_sealed
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Seals(c_int);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Seals {
+    /// Prevents the size from being reduced.
+    pub const SHRINK: Self = Self(libc::F_SEAL_SHRINK);
+    /// Prevents the size from being increased.
+    pub const GROW: Self = Self(libc::F_SEAL_GROW);
+    /// Prevents all writes, including through existing shared mappings.
+    pub const WRITE: Self = Self(libc::F_SEAL_WRITE);
+    /// Prevents new writes, while existing writable mappings remain usable.
+    pub const FUTURE_WRITE: Self = Self(libc::F_SEAL_FUTURE_WRITE);
+    /// Prevents any further seals from being added.
+    pub const SEAL: Self = Self(libc::F_SEAL_SEAL);
+
+    /// Creates an empty set of seals.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Gets the seals of any file descriptor.
+    ///
+    /// This fails for descriptors that do not support sealing.
+    pub fn of<F: AsFd>(fd: F) -> Result<Self> {
+        memfd_get_seals(fd.as_fd().as_raw_fd()).map(Self)
+    }
+
+    /// Adds the seals in this set to a descriptor.
+    pub(crate) fn add_to(self, fd: BorrowedFd<'_>) -> Result<()> {
+        memfd_add_seals(fd.as_raw_fd(), self.0)
+    }
+
+    /// Tests if there are no seals in the set.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Tests if all seals in `other` are also in this set.
+    #[inline]
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl BitOr for Seals {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl BitOrAssign for Seals {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
@@ -1,13 +1,13 @@
 use crate::{Advise, Flush, Protect};
 
 use std::fs::File;
-use std::os::unix::io::AsRawFd;
-use std::ptr;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd};
+use std::{mem, ptr};
 
 use libc::{
     c_void, madvise, mlock, mmap, mprotect, msync, munlock, munmap, off_t, sysconf, MADV_DONTNEED,
     MADV_NORMAL, MADV_RANDOM, MADV_SEQUENTIAL, MADV_WILLNEED, MAP_ANON, MAP_FAILED, MAP_PRIVATE,
-    MAP_SHARED, MS_ASYNC, MS_SYNC, PROT_EXEC, PROT_READ, PROT_WRITE, _SC_PAGESIZE,
+    MAP_SHARED, MS_ASYNC, MS_SYNC, PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE, _SC_PAGESIZE,
 };
 
 use crate::{Error, Operation, Result};
@@ -23,10 +23,21 @@ mod mach;
 pub use self::mach::{map_ring, unmap_ring};
 
 // For non-mach targets load the POSIX version of the ring mapping functions.
-#[cfg(all(feature = "io", not(any(target_os = "macos", target_os = "ios"))))]
+// The file descriptor based rings and memory file descriptors are available
+// on all targets.
 mod posix;
 #[cfg(all(feature = "io", not(any(target_os = "macos", target_os = "ios"))))]
 pub use self::posix::{map_ring, unmap_ring};
+#[cfg(feature = "io")]
+pub use self::posix::{map_ring_fd, tmp_open};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::posix::{memfd_add_seals, memfd_get_seals};
+pub use self::posix::{memfd_open, memfd_open_huge};
+
+#[cfg(feature = "io")]
+mod scm;
+#[cfg(feature = "io")]
+pub use self::scm::{recv_fd, send_fd};
 
 /// Requests the page size and allocation granularity from the system.
 pub fn system_info() -> (u32, u32) {
@@ -44,6 +55,11 @@ fn result(op: Operation, pg: *mut c_void) -> Result<*mut u8> {
 
 /// Memory maps a given range of a file.
 pub fn map_file(file: &File, off: usize, len: usize, prot: Protect) -> Result<*mut u8> {
+    map_fd(file.as_fd(), off, len, prot)
+}
+
+/// Memory maps a given range of a file descriptor.
+pub fn map_fd(fd: BorrowedFd<'_>, off: usize, len: usize, prot: Protect) -> Result<*mut u8> {
     let (prot, flags) = match prot {
         Protect::ReadOnly => (PROT_READ, MAP_SHARED),
         Protect::ReadWrite => (PROT_READ | PROT_WRITE, MAP_SHARED),
@@ -58,13 +74,34 @@ pub fn map_file(file: &File, off: usize, len: usize, prot: Protect) -> Result<*m
                 len,
                 prot,
                 flags,
-                file.as_raw_fd(),
+                fd.as_raw_fd(),
                 off as off_t,
             ),
         )
     }
 }
 
+/// Gets the size of the object referred to by a file descriptor.
+pub fn fd_len(fd: BorrowedFd<'_>) -> Result<usize> {
+    unsafe {
+        let mut st: libc::stat = mem::zeroed();
+        if libc::fstat(fd.as_raw_fd(), &mut st) < 0 {
+            Err(Error::last_os_error(MapFile))
+        } else {
+            Ok(st.st_size as usize)
+        }
+    }
+}
+
+/// Sets the size of the object referred to by a file descriptor.
+pub fn fd_set_len(fd: BorrowedFd<'_>, len: usize) -> Result<()> {
+    if unsafe { libc::ftruncate(fd.as_raw_fd(), len as off_t) } < 0 {
+        Err(Error::last_os_error(MapFile))
+    } else {
+        Ok(())
+    }
+}
+
 /// Creates an anonymous allocation.
 pub fn map_anon(len: usize, prot: Protect) -> Result<*mut u8> {
     let (prot, flags) = match prot {
@@ -76,6 +113,165 @@ pub fn map_anon(len: usize, prot: Protect) -> Result<*mut u8> {
     unsafe { result(MapAnonymous, mmap(ptr::null_mut(), len, prot, flags, -1, 0)) }
 }
 
+/// Creates an anonymous allocation using explicit huge pages.
+///
+/// The `size` is the huge page size in bytes, and it must be one of the
+/// sizes supported by the system. The length must be a multiple of `size`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn map_anon_huge(len: usize, prot: Protect, size: usize) -> Result<*mut u8> {
+    let (prot, flags) = match prot {
+        Protect::ReadOnly => (PROT_READ, MAP_ANON | MAP_SHARED),
+        Protect::ReadWrite => (PROT_READ | PROT_WRITE, MAP_ANON | MAP_SHARED),
+        Protect::ReadCopy => (PROT_READ | PROT_WRITE, MAP_ANON | MAP_PRIVATE),
+        Protect::ReadExec => (PROT_READ | PROT_EXEC, MAP_ANON | MAP_PRIVATE),
+    };
+    let flags =
+        flags | libc::MAP_HUGETLB | (size.trailing_zeros() as libc::c_int) << libc::MAP_HUGE_SHIFT;
+    unsafe { result(MapHugePage, mmap(ptr::null_mut(), len, prot, flags, -1, 0)) }
+}
+
+/// Creates an anonymous allocation using explicit huge pages.
+///
+/// Explicit huge pages are not supported on this platform, so this always
+/// results in an error with a kind of `Unsupported`.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn map_anon_huge(_len: usize, _prot: Protect, _size: usize) -> Result<*mut u8> {
+    Err(Error::io(
+        MapHugePage,
+        std::io::ErrorKind::Unsupported.into(),
+    ))
+}
+
+/// Requests the default huge page size from the system.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn huge_page_size() -> Option<usize> {
+    std::fs::read_to_string("/proc/meminfo")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("Hugepagesize:"))
+        .and_then(parse_kb)
+}
+
+/// Requests the default huge page size from the system.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn huge_page_size() -> Option<usize> {
+    None
+}
+
+/// Requests all supported huge page sizes from the system in ascending order.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn huge_page_sizes() -> Vec<usize> {
+    let mut sizes = std::fs::read_dir("/sys/kernel/mm/hugepages")
+        .map(|dir| {
+            dir.filter_map(|ent| {
+                let name = ent.ok()?.file_name();
+                parse_kb(name.to_str()?.strip_prefix("hugepages-")?)
+            })
+            .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    sizes.sort_unstable();
+    sizes
+}
+
+/// Requests all supported huge page sizes from the system in ascending order.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn huge_page_sizes() -> Vec<usize> {
+    Vec::new()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_kb(val: &str) -> Option<usize> {
+    let kb = val
+        .trim()
+        .strip_suffix("kB")?
+        .trim_end()
+        .parse::<usize>()
+        .ok()?;
+    Some(kb << 10)
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+))]
+const MAP_NORESERVE: libc::c_int = libc::MAP_NORESERVE;
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+const MAP_NORESERVE: libc::c_int = 0;
+
+/// Reserves an inaccessible range of address space.
+///
+/// No memory is committed for the range. Pages must be made accessible
+/// using [`commit`] before use.
+///
+/// [`commit`]: fn.commit.html
+pub fn map_reserve(len: usize) -> Result<*mut u8> {
+    let flags = MAP_ANON | MAP_PRIVATE | MAP_NORESERVE;
+    unsafe { result(Reserve, mmap(ptr::null_mut(), len, PROT_NONE, flags, -1, 0)) }
+}
+
+/// Commits a page range within a reserved range for read and write access.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn commit(pg: *mut u8, len: usize) -> Result<()> {
+    if mprotect(pg as *mut c_void, len, PROT_READ | PROT_WRITE) != 0 {
+        Err(Error::last_os_error(Commit))
+    } else {
+        Ok(())
+    }
+}
+
+/// Releases the memory for a committed page range and makes it inaccessible.
+///
+/// The range is replaced with a new inaccessible anonymous mapping, so it
+/// remains reserved, and any later [`commit`] observes zeroed pages.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+///
+/// [`commit`]: fn.commit.html
+pub unsafe fn decommit(pg: *mut u8, len: usize) -> Result<()> {
+    let flags = MAP_ANON | MAP_PRIVATE | MAP_NORESERVE | libc::MAP_FIXED;
+    result(Decommit, mmap(pg as *mut c_void, len, PROT_NONE, flags, -1, 0)).map(|_| ())
+}
+
+/// Unmaps a reserved range created by `map_reserve`.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn unmap_reserve(pg: *mut u8, len: usize) -> Result<()> {
+    unmap(pg, len)
+}
+
 /// Unmaps a page range from a previos mapping.
 ///
 /// # Safety
@@ -95,6 +291,51 @@ pub unsafe fn unmap(pg: *mut u8, len: usize) -> Result<()> {
     }
 }
 
+/// Resizes a page range from a previous mapping.
+///
+/// When `may_move` is `true` the mapping may be relocated if it cannot be
+/// resized in place, in which case the new address is returned.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `old` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `old`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn remap(pg: *mut u8, old: usize, new: usize, may_move: bool) -> Result<*mut u8> {
+    let flags = if may_move { libc::MREMAP_MAYMOVE } else { 0 };
+    result(Remap, libc::mremap(pg as *mut c_void, old, new, flags))
+}
+
+/// Resizes a page range from a previous mapping.
+///
+/// Without `mremap` support, a mapping may only be shrunk in place. Growing
+/// a mapping results in an error with a kind of `Unsupported`.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `old` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `old`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub unsafe fn remap(pg: *mut u8, old: usize, new: usize, _may_move: bool) -> Result<*mut u8> {
+    if new > old {
+        Err(Error::io(Remap, std::io::ErrorKind::Unsupported.into()))
+    } else if new < old && munmap(pg.add(new) as *mut c_void, old - new) < 0 {
+        Err(Error::last_os_error(Remap))
+    } else {
+        Ok(pg)
+    }
+}
+
 /// Changes the protection for a page range.
 ///
 /// # Safety
@@ -155,13 +396,7 @@ pub unsafe fn flush(pg: *mut u8, _file: &File, len: usize, mode: Flush) -> Resul
 /// Generally don't use this unless you are entirely sure you are
 /// doing so correctly.
 pub unsafe fn advise(pg: *mut u8, len: usize, adv: Advise) -> Result<()> {
-    let adv = match adv {
-        Advise::Normal => MADV_NORMAL,
-        Advise::Sequential => MADV_SEQUENTIAL,
-        Advise::Random => MADV_RANDOM,
-        Advise::WillNeed => MADV_WILLNEED,
-        Advise::WillNotNeed => MADV_DONTNEED,
-    };
+    let adv = advice(adv)?;
 
     if madvise(pg as *mut c_void, len, adv) < 0 {
         Err(Error::last_os_error(Advise))
@@ -170,6 +405,80 @@ pub unsafe fn advise(pg: *mut u8, len: usize, adv: Advise) -> Result<()> {
     }
 }
 
+fn advice(adv: Advise) -> Result<libc::c_int> {
+    match adv {
+        Advise::Normal => Ok(MADV_NORMAL),
+        Advise::Sequential => Ok(MADV_SEQUENTIAL),
+        Advise::Random => Ok(MADV_RANDOM),
+        Advise::WillNeed => Ok(MADV_WILLNEED),
+        Advise::WillNotNeed => Ok(MADV_DONTNEED),
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        Advise::Free => Ok(libc::MADV_FREE),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::Remove => Ok(libc::MADV_REMOVE),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::DontFork => Ok(libc::MADV_DONTFORK),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::DoFork => Ok(libc::MADV_DOFORK),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::DontDump => Ok(libc::MADV_DONTDUMP),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::DoDump => Ok(libc::MADV_DODUMP),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::HugePage => Ok(libc::MADV_HUGEPAGE),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::NoHugePage => Ok(libc::MADV_NOHUGEPAGE),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::Cold => Ok(libc::MADV_COLD),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::PageOut => Ok(libc::MADV_PAGEOUT),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::WipeOnFork => Ok(libc::MADV_WIPEONFORK),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::PopulateRead => Ok(libc::MADV_POPULATE_READ),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Advise::PopulateWrite => Ok(libc::MADV_POPULATE_WRITE),
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::io(Advise, std::io::ErrorKind::Unsupported.into())),
+    }
+}
+
+/// Queries which pages of a page range are resident in physical memory.
+///
+/// The returned list contains an entry for each page in the range.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn resident(pg: *mut u8, len: usize) -> Result<Vec<bool>> {
+    let mut vec = vec![0u8; resident_len(len)];
+    if libc::mincore(pg as *mut c_void, len, vec.as_mut_ptr() as *mut _) < 0 {
+        Err(Error::last_os_error(Resident))
+    } else {
+        Ok(vec.into_iter().map(|v| v & 1 != 0).collect())
+    }
+}
+
+/// Gets the number of entries `mincore` writes for a range of `len` bytes.
+pub(crate) fn resident_len(len: usize) -> usize {
+    len.div_ceil(crate::Size::page().size(1))
+}
+
 /// Locks physical pages into memory.
 ///
 /// # Safety
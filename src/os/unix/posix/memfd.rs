@@ -1,18 +1,50 @@
+use std::ffi::CStr;
 use std::os::raw::c_int;
 
 use crate::{Error, Operation, Result};
 
+/// Opens an empty anonymous memory file descriptor.
+///
+/// The descriptor is opened with the close-on-exec flag set, and allows
+/// seals to be added. The `name` is only used for debugging, and may be
+/// ignored on targets without support for naming the descriptor. It is owned
+/// by the caller and must be closed when no longer needed.
 #[cfg(any(target_os = "linux", target_os = "android"))]
-pub fn memfd_open() -> Result<c_int> {
-    use std::os::raw::c_char;
-    const NAME: &[u8] = b"vmap\0";
-    let fd = unsafe {
-        libc::syscall(
-            libc::SYS_memfd_create,
-            NAME.as_ptr() as *const c_char,
-            libc::MFD_CLOEXEC,
-        )
-    };
+pub fn memfd_open(name: &CStr) -> Result<c_int> {
+    memfd_create(name, libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING)
+}
+
+/// Opens an empty anonymous memory file descriptor using explicit huge pages.
+///
+/// This is like [`memfd_open`], but the memory is allocated from the huge
+/// page pool for the page `size`. The length and any mapped offset of the
+/// descriptor must be a multiple of `size`.
+///
+/// [`memfd_open`]: fn.memfd_open.html
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn memfd_open_huge(name: &CStr, size: usize) -> Result<c_int> {
+    let flags = libc::MFD_CLOEXEC
+        | libc::MFD_ALLOW_SEALING
+        | libc::MFD_HUGETLB
+        | size.trailing_zeros() << libc::MFD_HUGE_SHIFT;
+    memfd_create(name, flags)
+}
+
+/// Opens an empty anonymous memory file descriptor using explicit huge pages.
+///
+/// Explicit huge pages are not supported on this platform, so this always
+/// results in an error with a kind of `Unsupported`.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn memfd_open_huge(_name: &CStr, _size: usize) -> Result<c_int> {
+    Err(Error::io(
+        Operation::MemoryFd,
+        std::io::ErrorKind::Unsupported.into(),
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn memfd_create(name: &CStr, flags: libc::c_uint) -> Result<c_int> {
+    let fd = unsafe { libc::syscall(libc::SYS_memfd_create, name.as_ptr(), flags) };
     if fd < 0 {
         Err(Error::last_os_error(Operation::MemoryFd))
     } else {
@@ -20,8 +52,40 @@ pub fn memfd_open() -> Result<c_int> {
     }
 }
 
+/// Adds seals to a memory file descriptor.
+///
+/// The `seals` are a combination of the `F_SEAL_*` flags. Seals may only be
+/// added, and adding fails once `F_SEAL_SEAL` is set.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn memfd_add_seals(fd: c_int, seals: c_int) -> Result<()> {
+    if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+        Err(Error::last_os_error(Operation::MemoryFd))
+    } else {
+        Ok(())
+    }
+}
+
+/// Gets the seals of a memory file descriptor.
+///
+/// This fails for descriptors that do not support sealing.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn memfd_get_seals(fd: c_int) -> Result<c_int> {
+    let seals = unsafe { libc::fcntl(fd, libc::F_GET_SEALS) };
+    if seals < 0 {
+        Err(Error::last_os_error(Operation::MemoryFd))
+    } else {
+        Ok(seals)
+    }
+}
+
+/// Opens an empty anonymous memory file descriptor.
+///
+/// The descriptor is opened with the close-on-exec flag set. The `name` is
+/// only used for debugging, and may be ignored on targets without support for
+/// naming the descriptor. It is owned by the caller and must be closed when
+/// no longer needed.
 #[cfg(target_os = "freebsd")]
-pub fn memfd_open() -> Result<c_int> {
+pub fn memfd_open(_name: &CStr) -> Result<c_int> {
     let fd = unsafe { libc::shm_open(libc::SHM_ANON, libc::O_RDWR, 0o600) };
     if fd < 0 {
         Err(Error::last_os_error(Operation::MemoryFd))
@@ -30,8 +94,14 @@ pub fn memfd_open() -> Result<c_int> {
     }
 }
 
+/// Opens an empty anonymous memory file descriptor.
+///
+/// The descriptor is opened with the close-on-exec flag set. The `name` is
+/// only used for debugging, and may be ignored on targets without support for
+/// naming the descriptor. It is owned by the caller and must be closed when
+/// no longer needed.
 #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
-pub fn memfd_open() -> Result<c_int> {
+pub fn memfd_open(_name: &CStr) -> Result<c_int> {
     const OFLAGS: c_int = libc::O_RDWR | libc::O_CREAT | libc::O_EXCL | libc::O_CLOEXEC;
     let mut path_bytes: [u8; 14] = *b"/vmap-XXXXXXX\0";
 
@@ -0,0 +1,131 @@
+use std::os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::{mem, ptr};
+
+use libc::{
+    c_int, c_void, cmsghdr, iovec, msghdr, recvmsg, sendmsg, MSG_CTRUNC, SCM_RIGHTS, SOL_SOCKET,
+};
+
+use crate::{Error, Operation, Result};
+
+use self::Operation::*;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_FLAGS: c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_FLAGS: c_int = 0;
+
+// Control message buffer with enough space and alignment for a single fd.
+#[repr(C)]
+union Control {
+    _hdr: cmsghdr,
+    buf: [u8; 64],
+}
+
+/// Sends a file descriptor over a Unix domain socket.
+///
+/// The descriptor is passed using an `SCM_RIGHTS` control message along with
+/// a single byte of data. The receiver gets a new descriptor that refers to
+/// the same open file description.
+pub fn send_fd(sock: &UnixStream, fd: BorrowedFd<'_>) -> Result<()> {
+    let mut data = [0u8; 1];
+    let mut ctl: Control = unsafe { mem::zeroed() };
+    let mut iov = iovec {
+        iov_base: data.as_mut_ptr() as *mut c_void,
+        iov_len: data.len(),
+    };
+
+    unsafe {
+        let mut msg: msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = ctl.buf.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = libc::CMSG_SPACE(mem::size_of::<c_int>() as u32) as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = SOL_SOCKET;
+        (*cmsg).cmsg_type = SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<c_int>() as u32) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut c_int, fd.as_raw_fd());
+
+        if sendmsg(sock.as_raw_fd(), &msg, 0) < 0 {
+            Err(Error::last_os_error(SendFd))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Receives a file descriptor sent with [`send_fd`] over a Unix domain socket.
+///
+/// The received descriptor has the close-on-exec flag set. If the message
+/// carries more than one descriptor, only the first is returned, and the
+/// others are closed. A message with a truncated control message results in
+/// an error with a kind of `InvalidData`.
+///
+/// [`send_fd`]: fn.send_fd.html
+pub fn recv_fd(sock: &UnixStream) -> Result<OwnedFd> {
+    let mut data = [0u8; 1];
+    let mut ctl: Control = unsafe { mem::zeroed() };
+    let mut iov = iovec {
+        iov_base: data.as_mut_ptr() as *mut c_void,
+        iov_len: data.len(),
+    };
+
+    unsafe {
+        let mut msg: msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = ctl.buf.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = mem::size_of::<Control>() as _;
+
+        let n = recvmsg(sock.as_raw_fd(), &mut msg, RECV_FLAGS);
+        if n < 0 {
+            return Err(Error::last_os_error(ReceiveFd));
+        }
+
+        // Take ownership of every received descriptor so that any beyond the
+        // first are closed when dropped.
+        let mut fds = Vec::new();
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == SOL_SOCKET && (*cmsg).cmsg_type == SCM_RIGHTS {
+                let data = libc::CMSG_DATA(cmsg) as *const c_int;
+                let len = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                for i in 0..len / mem::size_of::<c_int>() {
+                    fds.push(OwnedFd::from_raw_fd(ptr::read_unaligned(data.add(i))));
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+
+        if msg.msg_flags & MSG_CTRUNC != 0 {
+            return Err(Error::io(ReceiveFd, std::io::ErrorKind::InvalidData.into()));
+        }
+        if n == 0 || fds.is_empty() {
+            return Err(Error::io(
+                ReceiveFd,
+                std::io::ErrorKind::UnexpectedEof.into(),
+            ));
+        }
+
+        let fd = fds.swap_remove(0);
+        set_cloexec(&fd)?;
+        Ok(fd)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_cloexec(_fd: &OwnedFd) -> Result<()> {
+    // Already set atomically using `MSG_CMSG_CLOEXEC`.
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_cloexec(fd: &OwnedFd) -> Result<()> {
+    if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+        Err(Error::last_os_error(ReceiveFd))
+    } else {
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use crate::{Advise, Flush, Protect};
 use std::os::windows::raw::HANDLE;
 
 use std::fs::File;
+use std::io;
 use std::os::raw::c_void;
 use std::os::windows::io::AsRawHandle;
 use std::{mem, ptr};
@@ -17,8 +18,8 @@ use winapi::um::memoryapi::{
 };
 use winapi::um::sysinfoapi::{GetSystemInfo, LPSYSTEM_INFO, SYSTEM_INFO};
 use winapi::um::winnt::{
-    MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READ, PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE,
-    PAGE_WRITECOPY,
+    MEM_COMMIT, MEM_DECOMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READ, PAGE_NOACCESS,
+    PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
 };
 
 use crate::{Error, Operation, Result};
@@ -128,6 +129,24 @@ pub fn map_anon(len: usize, prot: Protect) -> Result<*mut u8> {
     }
 }
 
+/// Creates an anonymous allocation using explicit huge pages.
+///
+/// Large pages are not supported by this library on Windows, so this always
+/// results in an error with a kind of `Unsupported`.
+pub fn map_anon_huge(_len: usize, _prot: Protect, _size: usize) -> Result<*mut u8> {
+    Err(Error::io(MapHugePage, io::ErrorKind::Unsupported.into()))
+}
+
+/// Requests the default huge page size from the system.
+pub fn huge_page_size() -> Option<usize> {
+    None
+}
+
+/// Requests all supported huge page sizes from the system in ascending order.
+pub fn huge_page_sizes() -> Vec<usize> {
+    Vec::new()
+}
+
 unsafe fn reserve(len: usize) -> Result<*mut c_void> {
     let pg = VirtualAlloc(ptr::null_mut(), len as SIZE_T, MEM_RESERVE, PAGE_NOACCESS);
     if pg.is_null() {
@@ -177,6 +196,83 @@ pub fn map_ring(len: usize) -> Result<*mut u8> {
     }
 }
 
+/// Reserves an inaccessible range of address space.
+///
+/// No memory is committed for the range. Pages must be made accessible
+/// using [`commit`] before use.
+///
+/// [`commit`]: fn.commit.html
+pub fn map_reserve(len: usize) -> Result<*mut u8> {
+    let pg = unsafe { VirtualAlloc(ptr::null_mut(), len as SIZE_T, MEM_RESERVE, PAGE_NOACCESS) };
+    if pg.is_null() {
+        Err(Error::last_os_error(Reserve))
+    } else {
+        Ok(pg as *mut u8)
+    }
+}
+
+/// Commits a page range within a reserved range for read and write access.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn commit(pg: *mut u8, len: usize) -> Result<()> {
+    if VirtualAlloc(pg as *mut c_void, len as SIZE_T, MEM_COMMIT, PAGE_READWRITE).is_null() {
+        Err(Error::last_os_error(Commit))
+    } else {
+        Ok(())
+    }
+}
+
+/// Releases the memory for a committed page range and makes it inaccessible.
+///
+/// The range remains reserved, and any later [`commit`] observes zeroed
+/// pages.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+///
+/// [`commit`]: fn.commit.html
+pub unsafe fn decommit(pg: *mut u8, len: usize) -> Result<()> {
+    if VirtualFree(pg as *mut c_void, len as SIZE_T, MEM_DECOMMIT) == 0 {
+        Err(Error::last_os_error(Decommit))
+    } else {
+        Ok(())
+    }
+}
+
+/// Unmaps a reserved range created by `map_reserve`.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn unmap_reserve(pg: *mut u8, _len: usize) -> Result<()> {
+    if VirtualFree(pg as *mut c_void, 0, MEM_RELEASE) == 0 {
+        Err(Error::last_os_error(Unmap))
+    } else {
+        Ok(())
+    }
+}
+
 /// Unmaps a page range from a previos mapping.
 ///
 /// # Safety
@@ -196,6 +292,29 @@ pub unsafe fn unmap(pg: *mut u8, _len: usize) -> Result<()> {
     }
 }
 
+/// Resizes a page range from a previous mapping.
+///
+/// Views cannot be resized, so a mapping may only be shrunk in place, and the
+/// full view remains mapped until unmapped. Growing a mapping results in an
+/// error with a kind of `Unsupported`.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `old` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `old`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn remap(pg: *mut u8, old: usize, new: usize, _may_move: bool) -> Result<*mut u8> {
+    if new > old {
+        Err(Error::io(Remap, io::ErrorKind::Unsupported.into()))
+    } else {
+        Ok(pg)
+    }
+}
+
 /// Unmaps a ring mapping created by `map_ring`.
 ///
 /// # Safety
@@ -281,8 +400,33 @@ pub unsafe fn flush(pg: *mut u8, file: &File, len: usize, mode: Flush) -> Result
 ///
 /// Generally don't use this unless you are entirely sure you are
 /// doing so correctly.
-pub unsafe fn advise(_pg: *mut u8, _len: usize, _adv: Advise) -> Result<()> {
-    Ok(())
+pub unsafe fn advise(_pg: *mut u8, _len: usize, adv: Advise) -> Result<()> {
+    match adv {
+        Advise::Normal
+        | Advise::Sequential
+        | Advise::Random
+        | Advise::WillNeed
+        | Advise::WillNotNeed => Ok(()),
+        _ => Err(Error::io(Advise, io::ErrorKind::Unsupported.into())),
+    }
+}
+
+/// Queries which pages of a page range are resident in physical memory.
+///
+/// Residency queries are not supported on this platform, so this always
+/// results in an error with a kind of `Unsupported`.
+///
+/// # Safety
+///
+/// This does not know or care if `pg` or `len` are valid. That is,
+/// it may be null, not at a proper page boundary, point to a size
+/// different from `len`, or worse yet, point to a properly mapped
+/// pointer from some other allocation system.
+///
+/// Generally don't use this unless you are entirely sure you are
+/// doing so correctly.
+pub unsafe fn resident(_pg: *mut u8, _len: usize) -> Result<Vec<bool>> {
+    Err(Error::io(Resident, io::ErrorKind::Unsupported.into()))
 }
 
 /// Locks physical pages into memory.
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::slice;
+
+use crate::os::{commit, decommit, map_reserve, unmap_reserve};
+use crate::{Error, Input, Operation, Result, Size};
+
+/// Reserved range of virtual address space with on-demand committed pages.
+///
+/// The full range is reserved up front without committing any memory, so
+/// the address of every byte remains fixed for the lifetime of the
+/// `Reservation`. Pages are made accessible using [`.commit()`] as a
+/// structure grows, and their memory may be returned to the system using
+/// [`.decommit()`]. Accessing a page that is not committed will fault, so
+/// the safe accessors only return slices that are entirely committed.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::{Reservation, Size};
+///
+/// # fn main() -> vmap::Result<()> {
+/// let page = Size::page().size(1);
+/// let mut res = Reservation::new(1 << 30)?;
+/// assert_eq!(0, res.committed());
+/// assert!(res.get(0, 5).is_none());
+///
+/// res.commit(0, 5)?;
+/// assert_eq!(page, res.committed());
+/// res.get_mut(0, 5).unwrap().clone_from_slice(b"hello");
+/// assert_eq!(b"hello", res.get(0, 5).unwrap());
+///
+/// res.decommit(0, page)?;
+/// assert_eq!(0, res.committed());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`.commit()`]: #method.commit
+/// [`.decommit()`]: #method.decommit
+#[derive(Debug)]
+pub struct Reservation {
+    ptr: *mut u8,
+    len: usize,
+    // Committed page ranges as `start => end`, kept disjoint and coalesced.
+    ranges: BTreeMap<usize, usize>,
+}
+
+// SAFETY: The reserved range is exclusively owned by the `Reservation` and is
+// released only when dropped. Committed memory is only mutated through
+// `&mut self`, so the same rules apply as for a `MapMut`.
+unsafe impl Send for Reservation {}
+unsafe impl Sync for Reservation {}
+
+impl Reservation {
+    /// Reserves a range of address space at least as large as the hint.
+    ///
+    /// The length is rounded up to the allocation granularity. No pages are
+    /// committed initially.
+    pub fn new(hint: usize) -> Result<Self> {
+        if hint == 0 {
+            return Err(Error::input(Operation::Reserve, Input::InvalidRange));
+        }
+        let len = Size::alloc().round(hint);
+        let ptr = map_reserve(len)?;
+        Ok(Self {
+            ptr,
+            len,
+            ranges: BTreeMap::new(),
+        })
+    }
+
+    /// Get the length of the reserved range.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tests if the reserved range covers zero bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the pointer to the start of the reserved range.
+    #[inline]
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Get a mutable pointer to the start of the reserved range.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Commits the pages covering a byte range for read and write access.
+    ///
+    /// Every page that overlaps the range is committed. Newly committed pages
+    /// are zeroed, and pages that are already committed are left unchanged.
+    pub fn commit(&mut self, off: usize, len: usize) -> Result<()> {
+        let (first, count) = self.pages(Operation::Commit, off, len)?;
+        let last = first + count;
+        let page = Size::page().size(1);
+        let mut overlap: Vec<(usize, usize)> = self
+            .ranges
+            .range(..last)
+            .rev()
+            .take_while(|&(_, &end)| end > first)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+        overlap.reverse();
+        overlap.push((last, last));
+
+        let mut pg = first;
+        for (start, end) in overlap {
+            if pg < start {
+                unsafe { commit(self.ptr.add(pg * page), (start - pg) * page)? };
+                self.mark(pg, start, true);
+            }
+            pg = pg.max(end);
+        }
+        Ok(())
+    }
+
+    /// Decommits the pages covering a byte range.
+    ///
+    /// Every page that overlaps the range is released back to the system and
+    /// made inaccessible, including any bytes outside of the range that share
+    /// a page with it. The address range remains reserved, and the pages may
+    /// be committed again later.
+    pub fn decommit(&mut self, off: usize, len: usize) -> Result<()> {
+        let (first, count) = self.pages(Operation::Decommit, off, len)?;
+        let page = Size::page().size(1);
+        unsafe { decommit(self.ptr.add(first * page), count * page)? };
+        self.mark(first, first + count, false);
+        Ok(())
+    }
+
+    /// Gets the number of committed bytes in the reserved range.
+    pub fn committed(&self) -> usize {
+        let count: usize = self.ranges.iter().map(|(start, end)| end - start).sum();
+        count * Size::page().size(1)
+    }
+
+    /// Tests if every page covering a byte range is committed.
+    pub fn is_committed(&self, off: usize, len: usize) -> bool {
+        match self.pages(Operation::None, off, len) {
+            Ok((first, count)) => self
+                .ranges
+                .range(..=first)
+                .next_back()
+                .is_some_and(|(_, &end)| end >= first + count),
+            Err(_) => false,
+        }
+    }
+
+    /// Gets a slice of a byte range if it is entirely committed.
+    pub fn get(&self, off: usize, len: usize) -> Option<&[u8]> {
+        if self.is_committed(off, len) {
+            Some(unsafe { slice::from_raw_parts(self.ptr.add(off), len) })
+        } else {
+            None
+        }
+    }
+
+    /// Gets a mutable slice of a byte range if it is entirely committed.
+    pub fn get_mut(&mut self, off: usize, len: usize) -> Option<&mut [u8]> {
+        if self.is_committed(off, len) {
+            Some(unsafe { slice::from_raw_parts_mut(self.ptr.add(off), len) })
+        } else {
+            None
+        }
+    }
+
+    fn pages(&self, op: Operation, off: usize, len: usize) -> Result<(usize, usize)> {
+        if len == 0 || off >= self.len || self.len - off < len {
+            return Err(Error::input(op, Input::InvalidRange));
+        }
+        let page = Size::page().size(1);
+        let first = off / page;
+        let last = (off + len).div_ceil(page);
+        Ok((first, last - first))
+    }
+
+    /// Marks the pages from `first` up to `last` as committed or not,
+    /// merging or splitting any ranges that touch them.
+    fn mark(&mut self, first: usize, last: usize, committed: bool) {
+        let touching: Vec<(usize, usize)> = self
+            .ranges
+            .range(..=last)
+            .rev()
+            .take_while(|&(_, &end)| end >= first)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+
+        let (mut lo, mut hi) = (first, last);
+        for (start, end) in touching {
+            self.ranges.remove(&start);
+            if committed {
+                lo = lo.min(start);
+                hi = hi.max(end);
+            } else {
+                if start < first {
+                    self.ranges.insert(start, first);
+                }
+                if end > last {
+                    self.ranges.insert(last, end);
+                }
+            }
+        }
+        if committed {
+            self.ranges.insert(lo, hi);
+        }
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        unsafe { unmap_reserve(self.ptr, self.len) }.unwrap_or_default();
+    }
+}
@@ -0,0 +1,167 @@
+use std::ops::{Bound, Deref, RangeBounds};
+use std::sync::Arc;
+
+use crate::{Map, Span};
+
+/// Cheaply cloneable, read-only view into a shared [`Map`].
+///
+/// The mapping is reference counted, so a `SharedMap` may be sliced, split,
+/// and cloned without copying any data or mapping it again. The underlying
+/// region is unmapped only after the last view referencing it is dropped.
+/// When the `bytes` feature is enabled, a `SharedMap` can also be converted
+/// into a `bytes::Bytes` using the same mapping.
+///
+/// # Examples
+///
+/// ```
+/// use vmap::{Map, SharedMap};
+/// use std::path::PathBuf;
+/// # use std::fs;
+///
+/// # fn main() -> vmap::Result<()> {
+/// # let tmp = tempdir::TempDir::new("vmap")?;
+/// let path: PathBuf = /* path to file */
+/// # tmp.path().join("example");
+/// # fs::write(&path, b"this is a test")?;
+/// let (map, _file) = Map::with_options().open(&path)?;
+/// let shared = SharedMap::from(map);
+///
+/// let (head, tail) = shared.split_at(4);
+/// assert_eq!(b"this", &head[..]);
+/// assert_eq!(b"test", &tail.slice(6..)[..]);
+/// drop(shared);
+/// assert_eq!(b" is a test", &tail[..]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Map`]: struct.Map.html
+#[derive(Clone, Debug)]
+pub struct SharedMap {
+    map: Arc<Map>,
+    off: usize,
+    len: usize,
+}
+
+impl SharedMap {
+    /// Creates a new `SharedMap` covering an entire mapping.
+    pub fn new(map: Map) -> Self {
+        let len = map.len();
+        Self {
+            map: Arc::new(map),
+            off: 0,
+            len,
+        }
+    }
+
+    /// Gets a reference to the underlying mapping.
+    ///
+    /// This is the entire mapping, not just the range covered by this view.
+    #[inline]
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// Gets the offset of this view from the start of the mapping.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.off
+    }
+
+    /// Returns a view of a sub-range of this view.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is after its end.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end,
+            "range start must not be greater than end: {:?} <= {:?}",
+            start,
+            end,
+        );
+        assert!(
+            end <= self.len,
+            "range end out of bounds: {:?} <= {:?}",
+            end,
+            self.len,
+        );
+        Self {
+            map: Arc::clone(&self.map),
+            off: self.off + start,
+            len: end - start,
+        }
+    }
+
+    /// Splits the view into two at an index.
+    ///
+    /// The first view covers `[0, mid)`, and the second covers `[mid, len)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "split out of bounds: {:?} <= {:?}",
+            mid,
+            self.len,
+        );
+        (self.slice(..mid), self.slice(mid..))
+    }
+}
+
+impl From<Map> for SharedMap {
+    fn from(map: Map) -> Self {
+        Self::new(map)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<SharedMap> for bytes::Bytes {
+    /// Converts the view into a `Bytes` without copying.
+    ///
+    /// The `Bytes` holds a reference to the mapping, which is unmapped once
+    /// it and every other view is dropped.
+    fn from(map: SharedMap) -> Self {
+        bytes::Bytes::from_owner(map)
+    }
+}
+
+impl Span for SharedMap {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *const u8 {
+        unsafe { self.map.as_ptr().add(self.off) }
+    }
+}
+
+impl Deref for SharedMap {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.map[self.off..self.off + self.len]
+    }
+}
+
+impl AsRef<[u8]> for SharedMap {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.deref()
+    }
+}